@@ -0,0 +1,129 @@
+// Run with `cargo bench --features nightly`
+#![feature(test)]
+
+extern crate test;
+extern crate nmg_vulkan as nmg;
+
+use nmg::alg;
+use nmg::entity;
+use nmg::components::transform;
+use nmg::components::Component;
+
+const WIDE: usize = 1024; // Children of a single root
+const DEEP: usize = 64; // Length of a single chain
+
+fn add(
+    entities: &mut entity::Manager,
+    transforms: &mut transform::Manager,
+) -> entity::Handle {
+    let entity = entities.add();
+    transforms.register(entity);
+    transforms.set_position(entity, alg::Vec3::right());
+    entity
+}
+
+// One root with `WIDE` children
+fn wide() -> (transform::Manager, Vec<entity::Handle>) {
+    let mut entities = entity::Manager::new(WIDE + 1);
+    let mut transforms = transform::Manager::new(WIDE + 1);
+    let root = add(&mut entities, &mut transforms);
+
+    let mut handles = vec![root];
+
+    for _ in 0..WIDE {
+        let child = add(&mut entities, &mut transforms);
+        transforms.parent_raw(child, root);
+        handles.push(child);
+    }
+
+    transforms.update();
+    (transforms, handles)
+}
+
+// Chain of `DEEP` transforms, each parented to the previous one
+fn deep() -> (transform::Manager, Vec<entity::Handle>) {
+    let mut entities = entity::Manager::new(DEEP);
+    let mut transforms = transform::Manager::new(DEEP);
+    let mut handles = vec![add(&mut entities, &mut transforms)];
+
+    for i in 1..DEEP {
+        let child = add(&mut entities, &mut transforms);
+        transforms.parent_raw(child, handles[i - 1]);
+        handles.push(child);
+    }
+
+    transforms.update();
+    (transforms, handles)
+}
+
+#[bench]
+fn wide_move_root(b: &mut test::Bencher) {
+    let (mut transforms, handles) = wide();
+    let mut x = 0.0;
+
+    b.iter(|| {
+        x += 1.0;
+        transforms.set_position(handles[0], alg::Vec3::new(x, 0.0, 0.0));
+        transforms.update();
+    });
+}
+
+#[bench]
+fn wide_move_children(b: &mut test::Bencher) {
+    let (mut transforms, handles) = wide();
+    let mut x = 0.0;
+
+    b.iter(|| {
+        x += 1.0;
+
+        for child in &handles[1..] {
+            transforms.set_position(*child, alg::Vec3::new(x, 0.0, 0.0));
+        }
+
+        transforms.update();
+    });
+}
+
+#[bench]
+fn deep_move_root(b: &mut test::Bencher) {
+    let (mut transforms, handles) = deep();
+    let mut x = 0.0;
+
+    b.iter(|| {
+        x += 1.0;
+        transforms.set_position(handles[0], alg::Vec3::new(x, 0.0, 0.0));
+        transforms.update();
+    });
+}
+
+// Worst case for eager propagation: every link is set in the same frame
+#[bench]
+fn deep_move_all(b: &mut test::Bencher) {
+    let (mut transforms, handles) = deep();
+    let mut angle = 0.0;
+
+    b.iter(|| {
+        angle += 0.01;
+        let orientation = alg::Quat::axis_angle(alg::Vec3::up(), angle);
+
+        for entity in &handles {
+            transforms.set_orientation(*entity, orientation);
+        }
+
+        transforms.update();
+    });
+}
+
+// Reads of a dirty chain are resolved without a propagation pass
+#[bench]
+fn deep_read_dirty(b: &mut test::Bencher) {
+    let (mut transforms, handles) = deep();
+    let tip = handles[DEEP - 1];
+    let mut x = 0.0;
+
+    b.iter(|| {
+        x += 1.0;
+        transforms.set_position(handles[0], alg::Vec3::new(x, 0.0, 0.0));
+        test::black_box(transforms.get_position(tip));
+    });
+}
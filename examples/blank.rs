@@ -31,6 +31,7 @@ impl nmg::Update for Demo {
         time:  f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities:   &mut entity::Manager,
@@ -57,5 +58,8 @@ impl nmg::FixedUpdate for Demo {
 }
 
 fn main() {
-    nmg::go(vec![], Demo { })
+    if let Err(e) = nmg::go(vec![], Demo { }) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
@@ -50,6 +50,7 @@ impl nmg::Update for Demo {
         time:  f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities:   &mut entity::Manager,
@@ -77,7 +78,10 @@ impl nmg::Update for Demo {
 fn main() {
     let demo = Demo { pyramid: None };
     let model_data = get_models();
-    nmg::go(model_data, demo)
+    if let Err(e) = nmg::go(model_data, demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
 fn get_models() -> Vec<render::ModelData> {
@@ -8,6 +8,7 @@ use nmg::components;
 use nmg::components::Component;
 use nmg::input;
 use nmg::debug;
+use nmg::prefab;
 
 /* In debug mode, this demo will render in wireframe, with physics markers.
  * In release mode, it will render nothing!
@@ -29,44 +30,44 @@ impl nmg::Start for Demo {
         entities: &mut entity::Manager,
         components: &mut components::Container,
     ) {
-        let first = entities.add();
-        components.transforms.register(first);
-        components.softbodies.register(first);
-        components.softbodies.build_instance()
-            .make_box_limb(alg::Vec3::one())
-            .mass(10.0)
-            .for_entity(first);
+        /* Build limbs and joints from a prefab */
+
+        let mut mech = prefab::Prefab::new();
 
-        let second = entities.add();
-        components.transforms.register(second);
-        components.softbodies.register(second);
-        components.softbodies.build_instance()
-            .make_box_limb(alg::Vec3::one())
+        let body = mech.part()
+            .box_limb(alg::Vec3::one())
             .mass(10.0)
-            .for_entity(second);
+            .add();
 
-        let third = entities.add();
-        components.transforms.register(third);
-        components.softbodies.register(third);
-        components.softbodies.build_instance()
-            .make_box_limb(alg::Vec3::one())
+        let front = mech.part()
+            .box_limb(alg::Vec3::one())
             .mass(10.0)
-            .for_entity(third);
+            .add();
 
-        /* Create joints */
+        let back = mech.part()
+            .box_limb(alg::Vec3::one())
+            .mass(10.0)
+            .add();
 
-        components.softbodies.build_joint()
-            .with_parent(first)
+        mech.joint(body, front)
             .offset(alg::Vec3::fwd() * 0.5)
             .xyz(-45.0, 45.0)
-            .for_child(second);
+            .add();
 
-        components.softbodies.build_joint()
-            .with_parent(first)
+        mech.joint(body, back)
             .fwd(-alg::Vec3::fwd())
             .offset(-alg::Vec3::fwd() * 0.5)
             .xyz(-45.0, 45.0)
-            .for_child(third);
+            .add();
+
+        let limbs = mech.spawn(
+            alg::Vec3::zero(),
+            alg::Quat::id(),
+            entities,
+            components,
+        );
+
+        let (first, second, third) = (limbs[body], limbs[front], limbs[back]);
 
         /* Add planes */
 
@@ -100,6 +101,7 @@ impl nmg::Update for Demo {
         time: f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities: &mut entity::Manager,
@@ -158,5 +160,8 @@ fn main() {
         last_target: alg::Vec3::zero(),
     };
 
-    nmg::go(vec![], demo)
+    if let Err(e) = nmg::go(vec![], demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
@@ -50,6 +50,7 @@ impl nmg::Update for Demo {
         time:  f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities:   &mut entity::Manager,
@@ -104,7 +105,10 @@ fn main() {
     };
 
     let model_data = get_models();
-    nmg::go(model_data, demo)
+    if let Err(e) = nmg::go(model_data, demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
 // "Load" model(s)
@@ -64,6 +64,7 @@ impl nmg::Update for Demo {
         time: f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities: &mut entity::Manager,
@@ -129,5 +130,8 @@ fn main() {
     };
 
     // Demo only renders anything in debug mode
-    nmg::go(vec![], demo)
+    if let Err(e) = nmg::go(vec![], demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
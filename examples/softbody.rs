@@ -131,6 +131,7 @@ impl nmg::Update for Demo {
         time: f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities: &mut entity::Manager,
@@ -196,7 +197,10 @@ fn main() {
         mesh: mesh,
     };
 
-    nmg::go(model_data, demo)
+    if let Err(e) = nmg::go(model_data, demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
 fn get_models() -> Vec<render::ModelData> {
@@ -129,6 +129,7 @@ impl nmg::Update for Demo {
         time:  f64,
         delta: f64,
         metadata: nmg::Metadata,
+        control: &mut nmg::clock::Control,
         screen: nmg::ScreenData,
         parameters: &mut render::Parameters,
         entities:   &mut entity::Manager,
@@ -174,5 +175,8 @@ fn main() {
         camera: None,
     };
 
-    nmg::go(vec![], demo)
+    if let Err(e) = nmg::go(vec![], demo) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
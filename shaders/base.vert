@@ -1,6 +1,6 @@
 #version 450
 
-#define MAX_SOFTBODY_VERT 57
+#define MAX_SOFTBODY_VERT 55
 #define MAX_INSTANCE_LIGHTS 4
 
 layout(binding = 0) uniform shared_ubo {
@@ -10,6 +10,7 @@ layout(binding = 0) uniform shared_ubo {
 
 layout(binding = 1, std140) uniform instance_ubo {
   mat4 model;
+  mat4 normal;
   vec4 lights[MAX_INSTANCE_LIGHTS * 2];
   vec3 position_offsets[MAX_SOFTBODY_VERT];
   vec3 normal_offsets[MAX_SOFTBODY_VERT];
@@ -37,10 +38,8 @@ void main() {
   fragPosition = position.xyz;
   fragColor = inColor;
 
-  fragNormal = (
-    instance.model
-      * vec4(inNormal + instance.normal_offsets[index], 0)
-  ).xyz;
+  fragNormal = mat3(instance.normal)
+    * (inNormal + instance.normal_offsets[index]);
   fragNormal = normalize(fragNormal);
 
   gl_Position = shared_data.projection * shared_data.view * position;
@@ -1137,6 +1137,97 @@ impl Mat4 {
         )
     }
 
+    /// Split an affine transform into translation, rotation, and scale. \
+    /// Shear (e.g. from a rotated child of a non-uniformly scaled parent)
+    /// can't be represented and is discarded, so the rotation is always
+    /// orthonormal.
+    pub fn decompose(self) -> (Vec3, Quat, Vec3) {
+        let x = Vec3::new(self.x0, self.y0, self.z0);
+        let y = Vec3::new(self.x1, self.y1, self.z1);
+        let z = Vec3::new(self.x2, self.y2, self.z2);
+
+        // Gram-Schmidt
+        let right = x.norm();
+        let up = (y - right * y.dot(right)).norm();
+        let fwd = right.cross(up);
+
+        // Reflections end up in the sign of the last axis
+        let scale = Vec3::new(x.mag(), y.dot(up), z.dot(fwd));
+
+        (
+            self.to_position(),
+            Mat3::axes(right, up, fwd).to_quat(),
+            scale,
+        )
+    }
+
+    /// Inverse of an affine transform (i.e. not a projection)
+    pub fn inverse_affine(self) -> Mat4 {
+        let inverse = self.to_mat3().inverse();
+
+        let mut result = inverse * Mat4::id();
+        result.set_translation(inverse * -self.to_position());
+        result
+    }
+
+    /// General inverse, for projections. \
+    /// Prefer `inverse_affine` for transforms.
+    pub fn inverse(self) -> Mat4 {
+        let m = self;
+
+        // 2x2 determinants of the top two rows
+        let s0 = m.x0 * m.y1 - m.x1 * m.y0;
+        let s1 = m.x0 * m.y2 - m.x2 * m.y0;
+        let s2 = m.x0 * m.y3 - m.x3 * m.y0;
+        let s3 = m.x1 * m.y2 - m.x2 * m.y1;
+        let s4 = m.x1 * m.y3 - m.x3 * m.y1;
+        let s5 = m.x2 * m.y3 - m.x3 * m.y2;
+
+        // 2x2 determinants of the bottom two rows
+        let c0 = m.z0 * m.w1 - m.z1 * m.w0;
+        let c1 = m.z0 * m.w2 - m.z2 * m.w0;
+        let c2 = m.z0 * m.w3 - m.z3 * m.w0;
+        let c3 = m.z1 * m.w2 - m.z2 * m.w1;
+        let c4 = m.z1 * m.w3 - m.z3 * m.w1;
+        let c5 = m.z2 * m.w3 - m.z3 * m.w2;
+
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        debug_assert!(det != 0.0);
+        let inv = 1.0 / det;
+
+        Mat4::new(
+            ( m.y1 * c5 - m.y2 * c4 + m.y3 * c3) * inv,
+            (-m.x1 * c5 + m.x2 * c4 - m.x3 * c3) * inv,
+            ( m.w1 * s5 - m.w2 * s4 + m.w3 * s3) * inv,
+            (-m.z1 * s5 + m.z2 * s4 - m.z3 * s3) * inv,
+
+            (-m.y0 * c5 + m.y2 * c2 - m.y3 * c1) * inv,
+            ( m.x0 * c5 - m.x2 * c2 + m.x3 * c1) * inv,
+            (-m.w0 * s5 + m.w2 * s2 - m.w3 * s1) * inv,
+            ( m.z0 * s5 - m.z2 * s2 + m.z3 * s1) * inv,
+
+            ( m.y0 * c4 - m.y1 * c2 + m.y3 * c0) * inv,
+            (-m.x0 * c4 + m.x1 * c2 - m.x3 * c0) * inv,
+            ( m.w0 * s4 - m.w1 * s2 + m.w3 * s0) * inv,
+            (-m.z0 * s4 + m.z1 * s2 - m.z3 * s0) * inv,
+
+            (-m.y0 * c3 + m.y1 * c1 - m.y2 * c0) * inv,
+            ( m.x0 * c3 - m.x1 * c1 + m.x2 * c0) * inv,
+            (-m.w0 * s3 + m.w1 * s1 - m.w2 * s0) * inv,
+            ( m.z0 * s3 - m.z1 * s1 + m.z2 * s0) * inv,
+        )
+    }
+
+    /// Transform a point, dividing by the resulting scaling factor. \
+    /// Returns the factor alongside, which is negative for points behind
+    /// a perspective projection.
+    pub fn project(self, point: Vec3) -> (Vec3, f32) {
+        let w = self.w0 * point.x + self.w1 * point.y + self.w2 * point.z
+            + self.w3;
+
+        (self * point / w, w)
+    }
+
     // Returns view matrix (inverted)
     pub fn look_at_view(position: Vec3, target: Vec3, up: Vec3) -> Mat4 {
         let fwd = (target - position).norm();
@@ -1174,6 +1265,24 @@ impl Mat4 {
         )
     }
 
+    // Input: vertical size of the view volume, screen aspect ratio,
+    // near and far planes
+    pub fn orthographic(size: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let y_scale = 2. / size;
+        let x_scale = y_scale / aspect;
+
+        // Fit into Vulkan clip space (0-1)
+        let z_scale = 1. / (far - near);
+        let z_offset = -near / (far - near);
+
+        Mat4::new(
+            x_scale,      0.0,     0.0,      0.0,
+                0.0, -y_scale,     0.0,      0.0, // Flip for Vulkan
+                0.0,      0.0, z_scale, z_offset,
+                0.0,      0.0,     0.0,      1.0,
+        )
+    }
+
     pub fn infinite_perspective(fov: f32, aspect: f32) -> Mat4 {
         // Perspective scaling (rectilinear)
         let y_scale = 1. / (0.5 * fov).to_radians().tan();
@@ -1659,6 +1768,71 @@ impl Line {
     }
 }
 
+/// Six inward-facing planes of a view volume
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extract planes from a view-projection matrix (Vulkan clip space)
+    pub fn from_mat(mat: Mat4) -> Frustum {
+        let plane = |x: f32, y: f32, z: f32, w: f32| {
+            let normal = Vec3::new(x, y, z);
+            let inv = 1.0 / normal.mag();
+            Plane::new_raw(normal * inv, w * inv)
+        };
+
+        let m = mat;
+
+        Frustum {
+            planes: [
+                plane(m.w0 + m.x0, m.w1 + m.x1, m.w2 + m.x2, m.w3 + m.x3),
+                plane(m.w0 - m.x0, m.w1 - m.x1, m.w2 - m.x2, m.w3 - m.x3),
+                plane(m.w0 + m.y0, m.w1 + m.y1, m.w2 + m.y2, m.w3 + m.y3),
+                plane(m.w0 - m.y0, m.w1 - m.y1, m.w2 - m.y2, m.w3 - m.y3),
+                plane(m.z0, m.z1, m.z2, m.z3), // Near (depth 0 to 1)
+                plane(m.w0 - m.z0, m.w1 - m.z1, m.w2 - m.z2, m.w3 - m.z3),
+            ],
+        }
+    }
+
+    /// Conservative: may return true for spheres just outside a corner
+    #[inline]
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes.iter().all(|plane| plane.dist(center) >= -radius)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3, // Normalized
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction: direction.norm(),
+        }
+    }
+
+    #[inline]
+    pub fn at(self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance along the ray to the plane, if it is hit in front
+    pub fn intersect_plane(self, plane: Plane) -> Option<f32> {
+        let rate = plane.normal.dot(self.direction);
+        if rate == 0.0 { return None; } // Parallel
+
+        let distance = -plane.dist(self.origin) / rate;
+        if distance < 0.0 { None } else { Some(distance) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alg::*;
@@ -1884,6 +2058,112 @@ mod tests {
         assert!(error < 0.0001);
     }
 
+    #[test]
+    fn mat4_decompose() {
+        let (position, rotation, scale) = (
+            Vec3::new(4.0, 5.0, 6.0),
+            Quat::axis_angle(Vec3::new(7.0, 8.0, 9.0), 10.0),
+            Vec3::new(1.0, 2.0, 3.0),
+        );
+
+        let mat = Mat4::transform(position, rotation, scale);
+        let (p, r, s) = mat.decompose();
+
+        assert!(vec3_error(p, position) < 0.0001);
+        assert!(mat3_error(r.to_mat(), rotation.to_mat()) < 0.0001);
+        assert!(vec3_error(s, scale) < 0.0001);
+
+        // Rotated child of a non-uniform parent is sheared
+        let sheared = Mat4::scale(1.0, 4.0, 1.0) * mat;
+        let (_, r, _) = sheared.decompose();
+        assert!((r.to_mat().det() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn mat4_inverse_affine() {
+        let mat = Mat4::scale(1.0, 4.0, 1.0) * Mat4::transform(
+            Vec3::new(4.0, 5.0, 6.0),
+            Quat::axis_angle(Vec3::new(7.0, 8.0, 9.0), 10.0),
+            Vec3::new(1.0, 2.0, 3.0),
+        );
+
+        let error = mat4_error(mat.inverse_affine() * mat, Mat4::id());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn mat4_orthographic() {
+        let projection = Mat4::orthographic(4.0, 2.0, 1.0, 9.0);
+
+        // Top right corner of the near plane
+        let corner = projection * Vec3::new(4.0, 2.0, 1.0);
+        assert!(vec3_error(corner, Vec3::new(1.0, -1.0, 0.0)) < 0.0001);
+
+        // Center of the far plane
+        let center = projection * Vec3::new(0.0, 0.0, 9.0);
+        assert!(vec3_error(center, Vec3::new(0.0, 0.0, 1.0)) < 0.0001);
+    }
+
+    #[test]
+    fn mat4_inverse() {
+        let mat = Mat4::perspective(60.0, 1.5, 0.1, 10.0) * Mat4::transform(
+            Vec3::new(4.0, 5.0, 6.0),
+            Quat::axis_angle(Vec3::new(7.0, 8.0, 9.0), 10.0),
+            Vec3::new(1.0, 2.0, 3.0),
+        );
+
+        let error = mat4_error(mat.inverse() * mat, Mat4::id());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Round trip through the perspective divide
+        let point = Vec3::new(-1.0, 2.0, 3.0);
+        let (clip, w) = mat.project(point);
+        assert!(w > 0.0);
+
+        let (back, _) = mat.inverse().project(clip);
+        assert!(vec3_error(back, point) < 0.0001);
+    }
+
+    #[test]
+    fn frustum_sphere() {
+        let view = Mat4::translation(0.0, 0.0, 2.0); // Camera at z = -2
+        let frustum = Frustum::from_mat(
+            Mat4::perspective(90.0, 1.0, 1.0, 10.0) * view,
+        );
+
+        assert!(frustum.intersects_sphere(Vec3::zero(), 0.5));
+
+        // Behind and off to the side
+        assert!(!frustum.intersects_sphere(Vec3::new(0.0, 0.0, -4.0), 1.0));
+        assert!(!frustum.intersects_sphere(Vec3::new(6.0, 0.0, 2.0), 1.0));
+
+        // Straddling the edge
+        assert!(frustum.intersects_sphere(Vec3::new(4.5, 0.0, 2.0), 1.0));
+
+        // Beyond the far plane
+        let frustum = Frustum::from_mat(
+            Mat4::orthographic(4.0, 1.0, 1.0, 10.0) * view,
+        );
+
+        assert!(frustum.intersects_sphere(Vec3::new(0.0, 0.0, 8.0), 1.0));
+        assert!(!frustum.intersects_sphere(Vec3::new(0.0, 0.0, 10.0), 1.0));
+    }
+
+    #[test]
+    fn ray_plane() {
+        let ray = Ray::new(Vec3::new(0.0, 4.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let ground = Plane::new(Vec3::up(), 0.0);
+
+        let distance = ray.intersect_plane(ground).unwrap();
+        assert!(vec3_error(ray.at(distance), Vec3::new(4.0, 0.0, 0.0)) < 1e-4);
+
+        // Pointing away
+        let ray = Ray::new(ray.origin, Vec3::up());
+        assert!(ray.intersect_plane(ground).is_none());
+    }
+
     /* Quaternion */
 
     #[test]
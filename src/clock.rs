@@ -0,0 +1,214 @@
+use std;
+
+/// Fixed-framerate accumulator shared by the windowed and headless loops
+pub struct Clock {
+    accumulator: f64,
+    step: f64, // Seconds of frame time per fixed step
+    max_steps: u32, // Zero = unbounded
+}
+
+impl Clock {
+    pub fn new(step: f64, max_steps: u32) -> Clock {
+        debug_assert!(step > 0.0);
+
+        Clock {
+            accumulator: 0.0,
+            step,
+            max_steps,
+        }
+    }
+
+    /// Accumulate frame time and return the number of fixed steps to run,
+    /// along with any time dropped to stay within the substep limit
+    pub fn advance(&mut self, delta: f64) -> (u32, f64) {
+        self.accumulator += delta;
+
+        let mut steps = (self.accumulator / self.step).floor() as u32;
+        let mut dropped = 0.0;
+
+        // Avoid the spiral of death after a long stall
+        if self.max_steps > 0 && steps > self.max_steps {
+            dropped = (steps - self.max_steps) as f64 * self.step;
+            steps = self.max_steps;
+        }
+
+        self.accumulator -= steps as f64 * self.step + dropped;
+
+        (steps, dropped)
+    }
+
+    /// Same as `advance()`, honoring the time scale and pause state.
+    /// While paused, only steps requested via `Control::step()` are run.
+    pub fn advance_controlled(
+        &mut self,
+        delta: f64,
+        control: &mut Control,
+    ) -> (u32, f64) {
+        if control.paused {
+            return (control.take_steps(), 0.0);
+        }
+
+        self.advance(delta * control.scale)
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Change the fixed step, keeping accumulated time and the substep
+    /// limit
+    pub fn set_step(&mut self, step: f64) {
+        debug_assert!(step > 0.0);
+        self.step = step;
+    }
+
+    pub fn max_steps(&self) -> u32 {
+        self.max_steps
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+
+    /// Fraction of a fixed step left over in the accumulator,
+    /// in the range [0, 1)
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step) as f32
+    }
+}
+
+/// Runtime control over the fixed-update and physics loop. \
+/// `Update` and rendering keep running while the loop is paused.
+#[derive(Clone, Copy, Debug)]
+pub struct Control {
+    scale: f64,
+    paused: bool,
+    pending: u32, // Fixed steps requested while paused
+}
+
+impl Default for Control {
+    fn default() -> Control {
+        Control::new()
+    }
+}
+
+impl Control {
+    pub fn new() -> Control {
+        Control {
+            scale: 1.0,
+            paused: false,
+            pending: 0,
+        }
+    }
+
+    /// Multiplier applied to frame time before it reaches the fixed loop;
+    /// e.g. 0.5 runs the simulation at half speed
+    pub fn set_scale(&mut self, scale: f64) {
+        debug_assert!(scale >= 0.0);
+        self.scale = scale;
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume, discarding any steps that have not run yet
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending = 0;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause and run exactly `count` fixed steps on the next frame
+    pub fn step(&mut self, count: u32) {
+        self.paused = true;
+        self.pending += count;
+    }
+
+    fn take_steps(&mut self) -> u32 {
+        std::mem::replace(&mut self.pending, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clock::*;
+
+    #[test]
+    fn accumulate() {
+        let mut clock = Clock::new(0.01, 0);
+
+        assert!(clock.advance(0.004) == (0, 0.0));
+        assert!(clock.advance(0.004) == (0, 0.0));
+        assert!(clock.advance(0.004) == (1, 0.0));
+        assert!(clock.advance(0.025) == (2, 0.0));
+    }
+
+    #[test]
+    fn limit_steps() {
+        let mut clock = Clock::new(0.01, 4);
+
+        let (steps, dropped) = clock.advance(1.005);
+        assert!(steps == 4);
+        assert!((dropped - 0.96).abs() < 1e-9);
+
+        // Remainder is kept
+        assert!(clock.advance(0.006).0 == 1);
+        assert!((clock.alpha() - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn drop_stalled_time() {
+        let mut clock = Clock::new(::FIXED_DT as f64, 4);
+        let mut metadata = ::Metadata::new();
+
+        // Simulate a one second hitch
+        let (steps, dropped) = clock.advance(1.0);
+        metadata.drop_time(dropped);
+
+        assert!(steps == 4);
+        assert!(metadata.dropped_frames == 1);
+        assert!(metadata.dropped_time > 0.9);
+
+        // Frames within the limit drop nothing
+        let (_, dropped) = clock.advance(::FIXED_DT as f64);
+        metadata.drop_time(dropped);
+        assert!(metadata.dropped_frames == 1);
+    }
+
+    #[test]
+    fn pause_and_step() {
+        let step = ::FIXED_DT as f64;
+        let mut clock = Clock::new(step, 0);
+        let mut control = Control::new();
+        control.pause();
+
+        // Frame time is ignored while paused
+        assert!(clock.advance_controlled(1.0, &mut control).0 == 0);
+
+        control.step(3);
+        assert!(clock.advance_controlled(1.0, &mut control).0 == 3);
+        assert!(clock.advance_controlled(1.0, &mut control).0 == 0);
+
+        // Steps that have not run yet are discarded on resume
+        control.step(2);
+        control.resume();
+        control.set_scale(0.5);
+        assert!(clock.advance_controlled(step * 8.0, &mut control).0 == 4);
+    }
+}
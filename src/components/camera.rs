@@ -8,34 +8,53 @@ use components::transform;
 pub const DEFAULT_FOV: f32 = 60.0;
 pub const DEFAULT_NEAR: f32 = 0.01;
 pub const DEFAULT_FAR: f32 = 32.0;
+pub const DEFAULT_SIZE: f32 = 8.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Projection {
+    Perspective, // Uses the field of view
+    Orthographic, // Uses the size
+}
 
 #[derive(Copy, Clone)]
 pub struct Camera {
+    projection: Projection,
     fov: f32,
+    size: f32, // Height of the orthographic view volume, in world units
     near: f32,
     far: f32,
+    viewport: render::Viewport,
     overrule: Option<render::SharedUBO>,
 }
 
 impl Default for Camera {
     fn default() -> Camera {
         Camera {
+            projection: Projection::Perspective,
             fov: DEFAULT_FOV,
+            size: DEFAULT_SIZE,
             near: DEFAULT_NEAR,
             far: DEFAULT_FAR,
+            viewport: render::Viewport::full(),
             overrule: None,
         }
     }
 }
 
 pub struct Manager {
-    active: usize,
+    active: Vec<usize>, // Rendered in order, one viewport each
     // There will likely be few cameras
     instances: Vec<(entity::Handle, Camera)>,
+    slots: components::Slots,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
+        self.evict(entity);
+        self.slots.claim(entity);
+
         self.instances.push(
             (
                 entity,
@@ -44,6 +63,33 @@ impl components::Component for Manager {
         );
     }
 
+    fn unregister(&mut self, entity: entity::Handle) {
+        let found = self.instances.iter()
+            .position(|instance| instance.0 == entity);
+
+        if let Some(i) = found {
+            self.instances.remove(i);
+            let count = self.instances.len();
+
+            // Keep the same cameras active if possible
+            for active in &mut self.active {
+                if i < *active || *active >= count {
+                    *active = active.saturating_sub(1);
+                }
+            }
+
+            // Don't render the same camera twice
+            let mut seen = Vec::with_capacity(self.active.len());
+            self.active.retain(|active| {
+                let unique = !seen.contains(active);
+                seen.push(*active);
+                unique
+            });
+        }
+
+        self.slots.release(entity);
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         self.instances.iter().find(|instance| instance.0 == entity).is_some()
     }
@@ -53,13 +99,32 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Camera" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.slots.get(index)
+    }
+}
+
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = &'a Camera;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(self.instances.iter().map(|instance| instance.0))
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        self.instances.iter()
+            .find(|instance| instance.0 == entity)
+            .map(|instance| &instance.1)
+    }
 }
 
 impl Manager {
     pub fn new(hint: usize) -> Manager {
         Manager {
-            active: 0,
+            active: vec![0],
             instances: Vec::with_capacity(hint),
+            slots: components::Slots::new(hint),
         }
     }
 
@@ -74,7 +139,105 @@ impl Manager {
             }
         }
 
-        self.active = camera_index;
+        self.active = vec![camera_index];
+    }
+
+    /// Render several cameras at once, each into its own viewport. \
+    /// Later cameras are drawn on top of earlier ones. 3D text and debug
+    /// lines are drawn once, inside the main (first) camera's viewport;
+    /// labels cover the whole screen.
+    pub fn set_active_split(&mut self, camera_indices: &[usize]) {
+        #[cfg(debug_assertions)] {
+            if camera_indices.is_empty()
+                || camera_indices.len() > render::MAX_VIEWPORTS
+            {
+                panic!(
+                    "Attempted to set {} active cameras (maximum is {})",
+                    camera_indices.len(),
+                    render::MAX_VIEWPORTS,
+                );
+            }
+
+            for &i in camera_indices {
+                if i >= self.instances.len() {
+                    panic!(
+                        "Attempted to set active camera to invalid index {}",
+                        i,
+                    );
+                }
+            }
+        }
+
+        self.active = camera_indices.to_vec();
+    }
+
+    /// Index of this camera, as passed to `set_active()`
+    pub fn get_index(&self, entity: entity::Handle) -> usize {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .position(|instance| instance.0 == entity).unwrap()
+    }
+
+    /// Position of this camera among the active ones (the main camera is
+    /// first), or `None` if it is not rendered
+    pub fn get_active(&self, entity: entity::Handle) -> Option<usize> {
+        let index = self.get_index(entity);
+        self.active.iter().position(|&active| active == index)
+    }
+
+    /// Set the screen rectangle this camera renders into, when active
+    pub fn set_viewport(
+        &mut self,
+        entity: entity::Handle,
+        viewport: render::Viewport,
+    ) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.viewport = viewport;
+    }
+
+    pub fn get_viewport(&self, entity: entity::Handle) -> render::Viewport {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.viewport
+    }
+
+    /// Switch between perspective and orthographic projection
+    pub fn set_projection(
+        &mut self,
+        entity: entity::Handle,
+        projection: Projection,
+    ) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.projection = projection;
+    }
+
+    pub fn get_projection(&self, entity: entity::Handle) -> Projection {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.projection
+    }
+
+    /// Set vertical size of the orthographic view volume, in world units
+    pub fn set_size(&mut self, entity: entity::Handle, size: f32) {
+        debug_validate_entity!(self, entity);
+        debug_assert!(size > 0.0);
+
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.size = size;
+    }
+
+    pub fn get_size(&self, entity: entity::Handle) -> f32 {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.size
     }
 
     pub fn set_fov(&mut self, entity: entity::Handle, fov: f32) {
@@ -131,9 +294,10 @@ impl Manager {
             .1.overrule = Some(shared_ubo);
     }
 
-    /// Build a SharedUBO necessary for rendering from the active camera
+    /// Build a SharedUBO necessary for rendering from the main
+    /// (first active) camera
     pub(crate) fn compute(
-        &mut self,
+        &self,
         transforms: &transform::Manager,
         screen: ::ScreenData,
     ) -> render::SharedUBO {
@@ -144,15 +308,43 @@ impl Manager {
             }
         }
 
-        debug_assert!(self.active < self.instances.len());
+        self.compute_camera(self.active[0], transforms, screen)
+    }
+
+    /// Build a SharedUBO and viewport for each active camera, in order
+    pub(crate) fn compute_views(
+        &self,
+        transforms: &transform::Manager,
+        screen: ::ScreenData,
+    ) -> Vec<(render::SharedUBO, render::Viewport)> {
+        #[cfg(debug_assertions)] {
+            use components::Component;
+            if self.count() == 0 {
+                panic!("There are no cameras registered");
+            }
+        }
+
+        self.active.iter().map(|&i| (
+            self.compute_camera(i, transforms, screen),
+            self.instances[i].1.viewport,
+        )).collect()
+    }
+
+    fn compute_camera(
+        &self,
+        index: usize,
+        transforms: &transform::Manager,
+        screen: ::ScreenData,
+    ) -> render::SharedUBO {
+        debug_assert!(index < self.instances.len());
 
-        // Get active entity and camera
-        let (entity, camera) = self.instances[self.active];
+        // Get entity and camera
+        let (entity, camera) = self.instances[index];
 
         // Return overridden shared UBO if set
         if let Some(shared_ubo) = camera.overrule { return shared_ubo }
 
-        // Get transform data for active camera entity
+        // Get transform data for camera entity
         debug_validate_entity!(transforms, entity);
         let (position, orientation, _) = transforms.get(entity);
 
@@ -161,13 +353,188 @@ impl Manager {
         let view = orientation.conjugate().to_mat()
             * alg::Mat4::translation_vec(-position);
 
-        let projection = alg::Mat4::perspective(
-            camera.fov,
-            screen.width as f32 / screen.height as f32,
-            camera.near,
-            camera.far,
-        );
+        let aspect = camera.viewport.aspect(screen);
+
+        let projection = match camera.projection {
+            Projection::Perspective => alg::Mat4::perspective(
+                camera.fov,
+                aspect,
+                camera.near,
+                camera.far,
+            ),
+
+            Projection::Orthographic => alg::Mat4::orthographic(
+                camera.size,
+                aspect,
+                camera.near,
+                camera.far,
+            ),
+        };
 
         render::SharedUBO::new(view, projection)
     }
+
+    /// Build a worldspace ray from the main camera through a point on the
+    /// screen, in pixels from the top left (as in `input.cursor_coords`). \
+    /// The ray starts on the near plane.
+    pub fn screen_to_ray(
+        &self,
+        transforms: &transform::Manager,
+        screen: ::ScreenData,
+        point: alg::Vec2,
+    ) -> alg::Ray {
+        let shared_ubo = self.compute(transforms, screen);
+        let inverse = (shared_ubo.projection() * shared_ubo.view()).inverse();
+        let viewport = self.instances[self.active[0]].1.viewport;
+
+        // Vulkan clip space is y-down, like the screen
+        let x = point.x / screen.width as f32 - viewport.x;
+        let y = point.y / screen.height as f32 - viewport.y;
+        let x = 2.0 * x / viewport.width - 1.0;
+        let y = 2.0 * y / viewport.height - 1.0;
+
+        let (near, _) = inverse.project(alg::Vec3::new(x, y, 0.0));
+        let (far, _) = inverse.project(alg::Vec3::new(x, y, 1.0));
+
+        alg::Ray::new(near, far - near)
+    }
+
+    /// Project a worldspace point into pixels from the top left of the
+    /// screen, using the main camera. \
+    /// Returns `None` for points behind the camera.
+    pub fn world_to_screen(
+        &self,
+        transforms: &transform::Manager,
+        screen: ::ScreenData,
+        point: alg::Vec3,
+    ) -> Option<alg::Vec2> {
+        let shared_ubo = self.compute(transforms, screen);
+        let view_point = shared_ubo.view() * point;
+
+        // Left-handed, looking down positive z
+        if view_point.z <= 0.0 { return None; }
+
+        let (clip, _) = shared_ubo.projection().project(view_point);
+        let viewport = self.instances[self.active[0]].1.viewport;
+
+        Some(
+            alg::Vec2::new(
+                (viewport.x + (clip.x + 1.0) * 0.5 * viewport.width)
+                    * screen.width as f32,
+                (viewport.y + (clip.y + 1.0) * 0.5 * viewport.height)
+                    * screen.height as f32,
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use render;
+    use components::Component;
+    use components::transform;
+    use components::camera::*;
+
+    #[test]
+    fn picking() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut cameras = Manager::new(1);
+
+        let camera = entities.add();
+        transforms.register(camera);
+        cameras.register(camera);
+
+        transforms.set_position(camera, alg::Vec3::new(1.0, 2.0, -3.0));
+        transforms.set_orientation(
+            camera,
+            alg::Quat::axis_angle(alg::Vec3::up(), 0.5),
+        );
+
+        let screen = ::ScreenData { width: 800, height: 600 };
+        let center = alg::Vec2::new(400.0, 300.0);
+        let (position, orientation, _) = transforms.get(camera);
+
+        let projections = [Projection::Perspective, Projection::Orthographic];
+
+        for &projection in &projections {
+            cameras.set_projection(camera, projection);
+
+            // Center of the screen looks straight ahead
+            let ray = cameras.screen_to_ray(&transforms, screen, center);
+            let forward = orientation * alg::Vec3::fwd();
+            assert!(ray.direction.dist(forward) < 1e-4);
+
+            let ahead = position + forward * 5.0;
+            let pixel = cameras.world_to_screen(&transforms, screen, ahead)
+                .unwrap();
+            assert!(pixel.dist(center) < 1e-2);
+
+            // Points along a ray land back on the same pixel
+            let corner = alg::Vec2::new(100.0, 50.0);
+            let ray = cameras.screen_to_ray(&transforms, screen, corner);
+            let along = ray.at(4.0);
+            let pixel = cameras.world_to_screen(&transforms, screen, along)
+                .unwrap();
+            assert!(pixel.dist(corner) < 1e-2);
+
+            // Top of the screen is up
+            assert!(ray.direction.dot(orientation * alg::Vec3::up()) >= 0.0);
+
+            // Nothing behind the camera
+            let behind = position - forward;
+            assert!(
+                cameras.world_to_screen(&transforms, screen, behind).is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn split_screen() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut cameras = Manager::new(2);
+
+        let left = entities.add();
+        let right = entities.add();
+
+        for &camera in &[left, right] {
+            transforms.register(camera);
+            cameras.register(camera);
+        }
+
+        let screen = ::ScreenData { width: 800, height: 600 };
+        let full = cameras.compute_views(&transforms, screen);
+        assert!(full.len() == 1);
+
+        let half = render::Viewport::new(0.0, 0.0, 0.5, 1.0);
+        cameras.set_viewport(left, half);
+        cameras.set_viewport(right, render::Viewport::new(0.5, 0.0, 0.5, 1.0));
+        cameras.set_active_split(&[0, 1]);
+
+        let views = cameras.compute_views(&transforms, screen);
+        assert!(views.len() == 2);
+        assert!(views[0].1 == half);
+        assert!(cameras.get_viewport(right).x == 0.5);
+
+        // Half the width, twice the horizontal scale
+        let ratio = views[0].0.projection().x0 / full[0].0.projection().x0;
+        assert!((ratio - 2.0).abs() < 1e-4);
+
+        // Picking uses the main camera's viewport
+        let center = alg::Vec2::new(200.0, 300.0);
+        let ray = cameras.screen_to_ray(&transforms, screen, center);
+        assert!(ray.direction.dist(alg::Vec3::fwd()) < 1e-4);
+
+        let ahead = alg::Vec3::fwd() * 5.0;
+        let pixel = cameras.world_to_screen(&transforms, screen, ahead)
+            .unwrap();
+        assert!(pixel.dist(center) < 1e-2);
+
+        // Remaining camera is rendered once
+        cameras.unregister(left);
+        assert!(cameras.compute_views(&transforms, screen).len() == 1);
+    }
 }
@@ -0,0 +1,708 @@
+use alg;
+use entity;
+use components;
+use input;
+
+use components::transform;
+
+pub const DEFAULT_SENSITIVITY: f32 = 0.005; // Radians per mouse unit
+pub const DEFAULT_PITCH_LIMIT: f32 = 85.0; // Degrees
+
+/// Free-fly camera: WASD to move, Q/E to descend/ascend,
+/// left shift to move faster, and the mouse to look around
+#[derive(Copy, Clone)]
+pub struct Fly {
+    speed: f32, // Units per second
+    fast_factor: f32,
+    sensitivity: f32,
+    pitch_limit: f32, // Degrees from the horizon
+    angles: Option<(f32, f32)>, // Yaw and pitch; unset until the first update
+}
+
+/// Orbits a target entity: the mouse rotates around it,
+/// and up/down zoom in and out
+#[derive(Copy, Clone)]
+pub struct Orbit {
+    target: entity::Handle,
+    offset: alg::Vec3, // Pivot relative to the target position
+    distance: f32,
+    min_distance: f32,
+    max_distance: f32,
+    zoom_speed: f32, // Units per second
+    sensitivity: f32,
+    pitch_limit: f32,
+    angles: Option<(f32, f32)>, // Taken from the transform unless set
+}
+
+/// Third-person camera pulled toward the target's transform by a damped
+/// spring (softbodies included, since physics drives their transforms)
+#[derive(Copy, Clone)]
+pub struct Follow {
+    target: entity::Handle,
+    offset: alg::Vec3, // Worldspace offset from the target
+    look_offset: alg::Vec3, // Point to look at, relative to the target
+    stiffness: f32,
+    damping: f32,
+    position: Option<alg::Vec3>, // Unset until the first update
+    velocity: alg::Vec3,
+}
+
+impl Default for Fly {
+    fn default() -> Fly {
+        Fly {
+            speed: 4.0,
+            fast_factor: 4.0,
+            sensitivity: DEFAULT_SENSITIVITY,
+            pitch_limit: DEFAULT_PITCH_LIMIT,
+            angles: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum Controller {
+    Fly(Fly),
+    Orbit(Orbit),
+    Follow(Follow),
+}
+
+/// Builder pattern for fly controllers
+pub struct FlyBuilder<'a> {
+    manager: &'a mut Manager,
+    fly: Fly,
+}
+
+impl<'a> FlyBuilder<'a> {
+    pub fn new(manager: &'a mut Manager) -> FlyBuilder<'a> {
+        FlyBuilder {
+            manager,
+            fly: Fly::default(),
+        }
+    }
+
+    pub fn speed(&mut self, speed: f32) -> &mut FlyBuilder<'a> {
+        self.fly.speed = speed;
+        self
+    }
+
+    /// Speed multiplier while left shift is held
+    pub fn fast_factor(&mut self, factor: f32) -> &mut FlyBuilder<'a> {
+        self.fly.fast_factor = factor;
+        self
+    }
+
+    pub fn sensitivity(&mut self, sensitivity: f32) -> &mut FlyBuilder<'a> {
+        self.fly.sensitivity = sensitivity;
+        self
+    }
+
+    /// Maximum angle above or below the horizon, in degrees
+    pub fn pitch_limit(&mut self, degrees: f32) -> &mut FlyBuilder<'a> {
+        debug_assert!(degrees >= 0.0 && degrees < 90.0);
+        self.fly.pitch_limit = degrees;
+        self
+    }
+
+    /// Finalize
+    pub fn for_entity(&mut self, entity: entity::Handle) {
+        self.manager.set(entity, Controller::Fly(self.fly));
+    }
+}
+
+/// Builder pattern for orbit controllers
+pub struct OrbitBuilder<'a> {
+    manager: &'a mut Manager,
+    orbit: Orbit,
+}
+
+impl<'a> OrbitBuilder<'a> {
+    pub fn new(
+        manager: &'a mut Manager,
+        target: entity::Handle,
+    ) -> OrbitBuilder<'a> {
+        OrbitBuilder {
+            manager,
+            orbit: Orbit {
+                target,
+                offset: alg::Vec3::zero(),
+                distance: 4.0,
+                min_distance: 0.5,
+                max_distance: 16.0,
+                zoom_speed: 4.0,
+                sensitivity: DEFAULT_SENSITIVITY,
+                pitch_limit: DEFAULT_PITCH_LIMIT,
+                angles: None,
+            },
+        }
+    }
+
+    pub fn offset(&mut self, offset: alg::Vec3) -> &mut OrbitBuilder<'a> {
+        self.orbit.offset = offset;
+        self
+    }
+
+    /// Initial distance from the pivot
+    pub fn distance(&mut self, distance: f32) -> &mut OrbitBuilder<'a> {
+        self.orbit.distance = distance;
+        self
+    }
+
+    pub fn distance_limits(
+        &mut self,
+        min: f32,
+        max: f32,
+    ) -> &mut OrbitBuilder<'a> {
+        debug_assert!(min > 0.0 && min <= max);
+        self.orbit.min_distance = min;
+        self.orbit.max_distance = max;
+        self
+    }
+
+    pub fn zoom_speed(&mut self, speed: f32) -> &mut OrbitBuilder<'a> {
+        self.orbit.zoom_speed = speed;
+        self
+    }
+
+    pub fn sensitivity(&mut self, sensitivity: f32) -> &mut OrbitBuilder<'a> {
+        self.orbit.sensitivity = sensitivity;
+        self
+    }
+
+    /// Maximum angle above or below the horizon, in degrees
+    pub fn pitch_limit(&mut self, degrees: f32) -> &mut OrbitBuilder<'a> {
+        debug_assert!(degrees >= 0.0 && degrees < 90.0);
+        self.orbit.pitch_limit = degrees;
+        self
+    }
+
+    /// Initial angles around the pivot, in degrees. \
+    /// Defaults to the current orientation of the entity.
+    pub fn angles(&mut self, yaw: f32, pitch: f32) -> &mut OrbitBuilder<'a> {
+        self.orbit.angles = Some((yaw.to_radians(), pitch.to_radians()));
+        self
+    }
+
+    /// Finalize
+    pub fn for_entity(&mut self, entity: entity::Handle) {
+        let mut orbit = self.orbit;
+
+        orbit.distance = orbit.distance
+            .max(orbit.min_distance)
+            .min(orbit.max_distance);
+
+        self.manager.set(entity, Controller::Orbit(orbit));
+    }
+}
+
+/// Builder pattern for follow controllers
+pub struct FollowBuilder<'a> {
+    manager: &'a mut Manager,
+    follow: Follow,
+}
+
+impl<'a> FollowBuilder<'a> {
+    pub fn new(
+        manager: &'a mut Manager,
+        target: entity::Handle,
+    ) -> FollowBuilder<'a> {
+        FollowBuilder {
+            manager,
+            follow: Follow {
+                target,
+                offset: alg::Vec3::new(0.0, 2.0, -6.0),
+                look_offset: alg::Vec3::zero(),
+                stiffness: 32.0,
+                damping: 12.0,
+                position: None,
+                velocity: alg::Vec3::zero(),
+            },
+        }
+    }
+
+    /// Resting position relative to the target, in worldspace
+    pub fn offset(&mut self, offset: alg::Vec3) -> &mut FollowBuilder<'a> {
+        self.follow.offset = offset;
+        self
+    }
+
+    pub fn look_offset(
+        &mut self,
+        offset: alg::Vec3,
+    ) -> &mut FollowBuilder<'a> {
+        self.follow.look_offset = offset;
+        self
+    }
+
+    /// Spring constant; higher values catch up faster
+    pub fn stiffness(&mut self, stiffness: f32) -> &mut FollowBuilder<'a> {
+        debug_assert!(stiffness > 0.0);
+        self.follow.stiffness = stiffness;
+        self
+    }
+
+    /// Damping coefficient; `2 * sqrt(stiffness)` is critically damped
+    pub fn damping(&mut self, damping: f32) -> &mut FollowBuilder<'a> {
+        debug_assert!(damping >= 0.0);
+        self.follow.damping = damping;
+        self
+    }
+
+    /// Finalize
+    pub fn for_entity(&mut self, entity: entity::Handle) {
+        self.manager.set(entity, Controller::Follow(self.follow));
+    }
+}
+
+/// Drives camera (or any) transforms from player input. \
+/// Updated by the engine once per frame, after the game update and
+/// physics, overwriting the entity's transform.
+pub struct Manager {
+    // There will likely be few controllers
+    instances: Vec<(entity::Handle, Controller)>,
+    slots: components::Slots,
+}
+
+impl components::Component for Manager {
+    fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
+        self.evict(entity);
+        self.slots.claim(entity);
+
+        self.instances.push(
+            (
+                entity,
+                Controller::Fly(Fly::default()),
+            )
+        );
+    }
+
+    fn unregister(&mut self, entity: entity::Handle) {
+        self.instances.retain(|instance| instance.0 != entity);
+        self.slots.release(entity);
+    }
+
+    fn registered(&self, entity: entity::Handle) -> bool {
+        self.instances.iter().find(|instance| instance.0 == entity).is_some()
+    }
+
+    fn count(&self) -> usize {
+        self.instances.len()
+    }
+
+    #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Controller" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.slots.get(index)
+    }
+}
+
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = &'a Controller;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(self.instances.iter().map(|instance| instance.0))
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        self.instances.iter()
+            .find(|instance| instance.0 == entity)
+            .map(|instance| &instance.1)
+    }
+}
+
+impl Manager {
+    pub fn new(hint: usize) -> Manager {
+        Manager {
+            instances: Vec::with_capacity(hint),
+            slots: components::Slots::new(hint),
+        }
+    }
+
+    /// Get fly controller builder
+    pub fn build_fly(&mut self) -> FlyBuilder {
+        FlyBuilder::new(self)
+    }
+
+    /// Get builder for a controller orbiting `target`
+    pub fn build_orbit(&mut self, target: entity::Handle) -> OrbitBuilder {
+        OrbitBuilder::new(self, target)
+    }
+
+    /// Get builder for a controller following `target`
+    pub fn build_follow(&mut self, target: entity::Handle) -> FollowBuilder {
+        FollowBuilder::new(self, target)
+    }
+
+    fn set(&mut self, entity: entity::Handle, controller: Controller) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1 = controller;
+    }
+
+    // Targets are tracked at their rendered (interpolated) pose
+    pub(crate) fn update(
+        &mut self,
+        delta: f32,
+        alpha: f32,
+        input: &input::Manager,
+        transforms: &mut transform::Manager,
+    ) {
+        use components::Component;
+
+        for &mut (entity, ref mut controller) in &mut self.instances {
+            debug_validate_entity!(transforms, entity);
+
+            let (position, orientation) = match *controller {
+                Controller::Fly(ref mut fly) => {
+                    fly.update(
+                        delta,
+                        input,
+                        transforms.get_position(entity),
+                        transforms.get_orientation(entity),
+                    )
+                },
+
+                Controller::Orbit(ref mut orbit) => {
+                    // Target was destroyed
+                    if !transforms.registered(orbit.target) { continue; }
+
+                    let target = transforms
+                        .get_interpolated(orbit.target, alpha).0;
+
+                    let orientation = transforms.get_orientation(entity);
+                    orbit.update(delta, input, target, orientation)
+                },
+
+                Controller::Follow(ref mut follow) => {
+                    // Target was destroyed
+                    if !transforms.registered(follow.target) { continue; }
+
+                    let target = transforms
+                        .get_interpolated(follow.target, alpha).0;
+
+                    follow.update(delta, target)
+                },
+            };
+
+            transforms.set_world_position(entity, position);
+            transforms.set_world_orientation(entity, orientation);
+        }
+    }
+}
+
+// Apply mouse look to yaw and pitch, returning the resulting orientation.
+// Unset angles are taken from the current orientation.
+fn look(
+    angles: &mut Option<(f32, f32)>,
+    orientation: alg::Quat,
+    sensitivity: f32,
+    pitch_limit: f32,
+    input: &input::Manager,
+) -> alg::Quat {
+    let limit = pitch_limit.to_radians();
+    let (yaw, pitch) = angles.unwrap_or_else(|| yaw_pitch(orientation));
+
+    let yaw = yaw + input.mouse_delta.x * sensitivity;
+    let pitch = (pitch + input.mouse_delta.y * sensitivity)
+        .max(-limit)
+        .min(limit);
+
+    *angles = Some((yaw, pitch));
+
+    alg::Quat::axis_angle(alg::Vec3::up(), yaw)
+        * alg::Quat::axis_angle(alg::Vec3::right(), pitch)
+}
+
+// Inverse of the rotation built by `look()`; roll is discarded
+fn yaw_pitch(orientation: alg::Quat) -> (f32, f32) {
+    let fwd = orientation * alg::Vec3::fwd();
+    let pitch = (-fwd.y).max(-1.0).min(1.0).asin();
+
+    (fwd.x.atan2(fwd.z), pitch)
+}
+
+impl Fly {
+    fn update(
+        &mut self,
+        delta: f32,
+        input: &input::Manager,
+        position: alg::Vec3,
+        orientation: alg::Quat,
+    ) -> (alg::Vec3, alg::Quat) {
+        let orientation = look(
+            &mut self.angles,
+            orientation,
+            self.sensitivity,
+            self.pitch_limit,
+            input,
+        );
+
+        let axis = |positive: input::Key, negative: input::Key| {
+            let mut value = 0.0;
+            if input.key_held(positive) { value += 1.0; }
+            if input.key_held(negative) { value -= 1.0; }
+            value
+        };
+
+        let direction = orientation * alg::Vec3::new(
+            axis(input::Key::D, input::Key::A),
+            0.0,
+            axis(input::Key::W, input::Key::S),
+        ) + alg::Vec3::up() * axis(input::Key::E, input::Key::Q);
+
+        let speed = if input.key_held(input::Key::LShift) {
+            self.speed * self.fast_factor
+        } else { self.speed };
+
+        (position + direction.norm_safe() * speed * delta, orientation)
+    }
+}
+
+impl Orbit {
+    fn update(
+        &mut self,
+        delta: f32,
+        input: &input::Manager,
+        target: alg::Vec3,
+        orientation: alg::Quat,
+    ) -> (alg::Vec3, alg::Quat) {
+        let orientation = look(
+            &mut self.angles,
+            orientation,
+            self.sensitivity,
+            self.pitch_limit,
+            input,
+        );
+
+        if input.key_held(input::Key::Up) {
+            self.distance -= self.zoom_speed * delta;
+        }
+
+        if input.key_held(input::Key::Down) {
+            self.distance += self.zoom_speed * delta;
+        }
+
+        self.distance = self.distance
+            .max(self.min_distance)
+            .min(self.max_distance);
+
+        // Look at the pivot from behind
+        let pivot = target + self.offset;
+        let position = pivot - orientation * alg::Vec3::fwd() * self.distance;
+
+        (position, orientation)
+    }
+}
+
+impl Follow {
+    fn update(
+        &mut self,
+        delta: f32,
+        target: alg::Vec3,
+    ) -> (alg::Vec3, alg::Quat) {
+        let rest = target + self.offset;
+
+        // Start at rest
+        let position = self.position.unwrap_or(rest);
+
+        // Exact solution, so long frames can't overshoot
+        let (offset, velocity) = spring(
+            position - rest,
+            self.velocity,
+            self.stiffness,
+            self.damping,
+            delta,
+        );
+
+        let position = rest + offset;
+        self.velocity = velocity;
+        self.position = Some(position);
+
+        let orientation = alg::Quat::look_at(
+            position,
+            target + self.look_offset,
+            alg::Vec3::up(),
+        );
+
+        (position, orientation)
+    }
+}
+
+// Advance a damped spring (x'' = -stiffness * x - damping * x') at rest at
+// the origin by `t` seconds, returning the new offset and velocity.
+// Closed-form, so the result doesn't depend on the frame rate.
+fn spring(
+    offset: alg::Vec3,
+    velocity: alg::Vec3,
+    stiffness: f32,
+    damping: f32,
+    t: f32,
+) -> (alg::Vec3, alg::Vec3) {
+    let a = 0.5 * damping;
+    let discriminant = a * a - stiffness;
+    let decay = (-a * t).exp();
+
+    // Each result is a linear combination of the initial offset and velocity
+    let (x0, x1, v0, v1) = if discriminant < -1e-4 * stiffness {
+        // Underdamped
+        let w = (-discriminant).sqrt();
+        let (sin, cos) = (w * t).sin_cos();
+
+        (
+            decay * (cos + a * sin / w),
+            decay * sin / w,
+            -decay * stiffness * sin / w,
+            decay * (cos - a * sin / w),
+        )
+    } else if discriminant > 1e-4 * stiffness {
+        // Overdamped
+        let root = discriminant.sqrt();
+        let (r0, r1) = (-a + root, -a - root);
+        let (e0, e1) = ((r0 * t).exp(), (r1 * t).exp());
+        let span = r0 - r1;
+
+        (
+            (r0 * e1 - r1 * e0) / span,
+            (e0 - e1) / span,
+            r0 * r1 * (e1 - e0) / span,
+            (r0 * e0 - r1 * e1) / span,
+        )
+    } else {
+        // Critically damped
+        (
+            decay * (1.0 + a * t),
+            decay * t,
+            -decay * stiffness * t,
+            decay * (1.0 - a * t),
+        )
+    };
+
+    (
+        offset * x0 + velocity * x1,
+        offset * v0 + velocity * v1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use input;
+    use components::Component;
+    use components::transform;
+    use components::controller::*;
+
+    #[test]
+    fn follow_and_orbit() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = transform::Manager::new(3);
+        let mut controllers = Manager::new(2);
+        let input = input::Manager::new();
+
+        let target = entities.add();
+        let follower = entities.add();
+        let orbiter = entities.add();
+
+        for &entity in &[target, follower, orbiter] {
+            transforms.register(entity);
+        }
+
+        controllers.register(follower);
+        controllers.build_follow(target)
+            .offset(alg::Vec3::new(0.0, 1.0, -4.0))
+            .for_entity(follower);
+
+        controllers.register(orbiter);
+        controllers.build_orbit(target)
+            .distance(3.0)
+            .for_entity(orbiter);
+
+        // Jump the target, then let the spring settle
+        let goal = alg::Vec3::new(8.0, 0.0, 0.0);
+        controllers.update(0.01, 1.0, &input, &mut transforms);
+        transforms.set_position(target, goal);
+
+        controllers.update(0.01, 1.0, &input, &mut transforms);
+        let lagging = transforms.get_position(follower);
+        assert!(lagging.dist(goal + alg::Vec3::new(0.0, 1.0, -4.0)) > 1.0);
+
+        for _ in 0..1000 {
+            controllers.update(0.01, 1.0, &input, &mut transforms);
+        }
+
+        let settled = transforms.get_position(follower);
+        assert!(settled.dist(goal + alg::Vec3::new(0.0, 1.0, -4.0)) < 1e-2);
+
+        // Orbit sits behind the target, looking at it
+        let orbit = transforms.get_position(orbiter);
+        assert!(orbit.dist(goal - alg::Vec3::fwd() * 3.0) < 1e-4);
+
+        // Unregistering leaves the other controllers in place
+        controllers.unregister(follower);
+        assert!(!controllers.registered(follower));
+        assert!(controllers.count() == 1);
+    }
+
+    #[test]
+    fn keep_initial_orientation() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut controllers = Manager::new(2);
+        let input = input::Manager::new();
+
+        let orientation = alg::Quat::axis_angle(alg::Vec3::up(), 1.5)
+            * alg::Quat::axis_angle(alg::Vec3::right(), 0.5);
+
+        let flyer = entities.add();
+        transforms.register(flyer);
+        transforms.set_orientation(flyer, orientation);
+        controllers.register(flyer);
+        controllers.build_fly().for_entity(flyer);
+
+        let orbiter = entities.add();
+        transforms.register(orbiter);
+        transforms.set_orientation(orbiter, orientation);
+        controllers.register(orbiter);
+        controllers.build_orbit(flyer).for_entity(orbiter);
+
+        // No mouse movement, so neither controller should turn
+        controllers.update(0.01, 1.0, &input, &mut transforms);
+
+        let expected = orientation * alg::Vec3::fwd();
+
+        for &entity in &[flyer, orbiter] {
+            let fwd = transforms.get_orientation(entity) * alg::Vec3::fwd();
+            assert!(fwd.dist(expected) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn spring_long_frame() {
+        let offset = alg::Vec3::new(8.0, 0.0, 0.0);
+
+        // Overdamped, underdamped and critically damped
+        for &(stiffness, damping) in &[(32.0, 12.0), (32.0, 2.0), (4.0, 4.0)] {
+            // A single long frame matches many short ones
+            let (long, _) = spring(
+                offset, alg::Vec3::zero(), stiffness, damping, 0.5,
+            );
+
+            let mut short = (offset, alg::Vec3::zero());
+            for _ in 0..50 {
+                short = spring(short.0, short.1, stiffness, damping, 0.01);
+            }
+
+            assert!(long.dist(short.0) < 1e-3);
+
+            // Hitches settle instead of diverging
+            let (settled, velocity) = spring(
+                offset, alg::Vec3::zero(), stiffness, damping, 10.0,
+            );
+
+            assert!(settled.mag() < 1e-2);
+            assert!(velocity.mag() < 1e-2);
+        }
+    }
+}
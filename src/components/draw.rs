@@ -1,6 +1,5 @@
 extern crate fnv;
 
-use alg;
 use entity;
 use render;
 use components;
@@ -38,14 +37,28 @@ pub struct Manager {
         entity::Handle,
         Option<render::InstanceHandle>,
     >,
+    slots: components::Slots,
     pub instances: render::Instances,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
+        self.evict(entity);
+        self.slots.claim(entity);
+
         self.handles.insert(entity, None);
     }
 
+    fn unregister(&mut self, entity: entity::Handle) {
+        if let Some(Some(handle)) = self.handles.remove(&entity) {
+            self.instances.remove(handle);
+        }
+
+        self.slots.release(entity);
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         self.handles.contains_key(&entity)
     }
@@ -55,6 +68,26 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Draw" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.slots.get(index)
+    }
+}
+
+// Only yields entities with a bound model
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = render::InstanceHandle;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(
+            self.handles.iter()
+                .filter_map(|(entity, handle)| handle.map(|_| *entity))
+        )
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        self.handles.get(&entity).cloned().and_then(|handle| handle)
+    }
 }
 
 impl Manager {
@@ -65,12 +98,13 @@ impl Manager {
                 hint,
                 Default::default(),
             ),
+            slots: components::Slots::new(hint),
         }
     }
 
     /// Set model that the draw component will render for this entity,
     /// given the name of the model.
-    /// For now, this can only be done once.
+    /// Call `unbind()` first to change an existing model.
     pub fn bind_model(&mut self, entity: entity::Handle, name: &str) {
         let index = self.instances.get_index(name);
         self.bind_model_index(entity, index);
@@ -78,7 +112,7 @@ impl Manager {
 
     /// Set model that the draw component will render for this entity,
     /// given the unique index of the model.
-    /// For now, this can only be done once.
+    /// Call `unbind()` first to change an existing model.
     pub fn bind_model_index(
         &mut self,
         entity: entity::Handle,
@@ -95,6 +129,26 @@ impl Manager {
         *self.handles.get_mut(&entity).unwrap() = Some(handle);
     }
 
+    /// Remove the model bound to this entity, freeing its instance
+    /// (idempotent)
+    pub fn unbind(&mut self, entity: entity::Handle) {
+        debug_validate_entity!(self, entity);
+        let handle = self.handles.get_mut(&entity).unwrap();
+
+        if let Some(instance) = handle.take() {
+            self.instances.remove(instance);
+        }
+    }
+
+    /// Returns name of the model bound to this entity, if any
+    pub fn get_model(&self, entity: entity::Handle) -> Option<&str> {
+        debug_validate_entity!(self, entity);
+
+        self.handles[&entity].map(
+            |handle| self.instances.get_name(handle.model_index() as usize)
+        )
+    }
+
     /// Stop entity from being rendered
     pub fn hide(&mut self, entity: entity::Handle) {
         let handle = get_handle!(self, entity);
@@ -114,28 +168,30 @@ impl Manager {
     }
 
     // Update
+    // `alpha` blends physics-driven transforms between fixed steps
     pub(crate) fn transfer(
         &mut self,
         transforms: &transform::Manager,
         softbodies: &softbody::Manager,
         lights: &light::Manager,
+        alpha: f32,
     ) {
+        use components::Component;
+
         for (entity, instance) in &self.handles {
-            debug_validate_handle!(self, instance, entity);
+            // Skip entities without a bound model
+            let instance = match instance {
+                Some(instance) => *instance,
+                None => continue,
+            };
 
             // Get transform component data
             debug_validate_entity!(transforms, *entity);
-            let transform = transforms.get(*entity);
+            let model = transforms.get_mat_interpolated(*entity, alpha);
 
             // Build uniform buffer object
             let ubo = {
-                let model = alg::Mat4::transform(
-                    transform.0,
-                    transform.1,
-                    transform.2,
-                );
-
-                let instance_lights = lights.cull(transform.0);
+                let instance_lights = lights.cull(model.to_position());
 
                 render::InstanceUBO::new(
                     model,
@@ -145,8 +201,79 @@ impl Manager {
                 )
             };
 
+            // Softbody offsets can reach past the model bounds
+            let extent = if softbodies.registered(*entity) {
+                ubo.offset_extent()
+            } else { 0.0 };
+
             // Update renderer
-            self.instances.update(instance.unwrap(), ubo);
+            self.instances.update(instance, ubo);
+            self.instances.update_extent(instance, extent);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use entity;
+    use render;
+    use components::Component;
+    use components::draw::*;
+
+    #[test]
+    fn rebind_model() {
+        let names = vec!["cube".to_string(), "sphere".to_string()];
+        let mut entities = entity::Manager::new(2);
+        let mut draws = Manager::new(
+            2,
+            render::Instances::new(2, &names, None),
+        );
+
+        let entity = entities.add();
+        draws.register(entity);
+        draws.bind_model(entity, "cube");
+        assert!(draws.get_model(entity) == Some("cube"));
+
+        draws.unbind(entity);
+        draws.unbind(entity);
+        assert!(draws.get_model(entity).is_none());
+        assert!(draws.instances.live_count() == 0);
+
+        draws.bind_model(entity, "sphere");
+        assert!(draws.get_model(entity) == Some("sphere"));
+        assert!(draws.instances.live_count() == 1);
+
+        // Rebinding the first model reuses its freed slot
+        let other = entities.add();
+        draws.register(other);
+        draws.bind_model(other, "cube");
+        assert!(draws.instances.count() == 2);
+
+        draws.unregister(entity);
+        assert!(draws.instances.live_count() == 1);
+    }
+
+    #[test]
+    fn reuse_slot() {
+        let names = vec!["cube".to_string()];
+        let mut entities = entity::Manager::new(1);
+        let mut draws = Manager::new(
+            1,
+            render::Instances::new(1, &names, None),
+        );
+
+        let stale = entities.add();
+        draws.register(stale);
+        draws.bind_model(stale, "cube");
+
+        // Entity is destroyed without unregistering its draw component
+        entities.remove(stale);
+        let entity = entities.add();
+        assert!(entity.get_index() == stale.get_index());
+
+        draws.register(entity);
+        assert!(draws.count() == 1);
+        assert!(!draws.registered(stale));
+        assert!(draws.instances.live_count() == 0);
+    }
+}
@@ -104,16 +104,27 @@ impl<'a> LightBuilder<'a> {
 
 pub struct Manager {
     instances: fnv::FnvHashMap<entity::Handle, render::Light>,
+    slots: components::Slots,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
+        self.evict(entity);
+        self.slots.claim(entity);
+
         self.instances.insert(
             entity,
             render::Light::default(),
         );
     }
 
+    fn unregister(&mut self, entity: entity::Handle) {
+        self.instances.remove(&entity);
+        self.slots.release(entity);
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         self.instances.contains_key(&entity)
     }
@@ -123,6 +134,22 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Light" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.slots.get(index)
+    }
+}
+
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = &'a render::Light;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(self.instances.keys().cloned())
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        self.instances.get(&entity)
+    }
 }
 
 impl Manager {
@@ -132,6 +159,7 @@ impl Manager {
                 hint,
                 Default::default(),
             ),
+            slots: components::Slots::new(hint),
         }
     }
 
@@ -141,7 +169,11 @@ impl Manager {
         LightBuilder::new(self)
     }
 
-    fn set(&mut self, entity: entity::Handle, light: render::Light) {
+    pub(crate) fn set(
+        &mut self,
+        entity: entity::Handle,
+        light: render::Light,
+    ) {
         debug_validate_entity!(self, entity);
         *self.instances.get_mut(&entity).unwrap() = light;
     }
@@ -180,11 +212,16 @@ impl Manager {
     }
 
     /// Update point light positions from transform component
-    pub(crate) fn update(&mut self, transforms: &transform::Manager) {
+    /// Follow the interpolated (rendered) pose of each point light
+    pub(crate) fn update(
+        &mut self,
+        transforms: &transform::Manager,
+        alpha: f32,
+    ) {
         for (entity, light) in &mut self.instances {
             if light.radius > 0.0 {
                 debug_validate_entity!(transforms, *entity);
-                light.vector = transforms.get_position(*entity);
+                light.vector = transforms.get_interpolated(*entity, alpha).0;
             }
         }
     }
@@ -6,6 +6,20 @@ macro_rules! debug_validate_entity {
             if !$component.registered($entity) {
                 let call = fn_name!();
 
+                // Index is in use by a different generation
+                if let Some(live) = $component.find(
+                    $entity.get_index()
+                ) {
+                    panic!(
+                        "Stale handle {} (live entity is {}) \
+                        used with {} component in {}(...)",
+                        $entity,
+                        live,
+                        $component.debug_name(),
+                        &call[12..call.len()]
+                    );
+                }
+
                 panic!(
                     "{} component not found for entity {} in {}(...)",
                     $component.debug_name(),
@@ -25,16 +39,68 @@ pub mod softbody;
 pub mod bitmap;
 pub mod text;
 pub mod label;
+pub mod controller;
+pub mod query;
 
 use entity;
 
 pub trait Component {
     fn register(&mut self, entity: entity::Handle);
+    fn unregister(&mut self, entity: entity::Handle); // Idempotent
     fn registered(&self, entity: entity::Handle) -> bool;
     fn count(&self) -> usize;
 
     #[cfg(debug_assertions)]
     fn debug_name(&self) -> &str;
+
+    /// Returns the handle registered at the given index, if any
+    fn find(&self, index: u32) -> Option<entity::Handle>;
+
+    /// Unregister whatever a previous generation left at the index of
+    /// `entity` (e.g. after `entity::Manager::remove()` without
+    /// `Container::destroy()`). Called first thing in `register()`.
+    fn evict(&mut self, entity: entity::Handle) {
+        if let Some(previous) = self.find(entity.get_index()) {
+            self.unregister(previous);
+        }
+    }
+}
+
+/// Maps entity indices to the handle a manager holds there,
+/// for managers that aren't already indexed by entity
+pub(crate) struct Slots {
+    handles: Vec<Option<entity::Handle>>,
+}
+
+impl Slots {
+    pub(crate) fn new(hint: usize) -> Slots {
+        Slots {
+            handles: Vec::with_capacity(hint),
+        }
+    }
+
+    pub(crate) fn claim(&mut self, entity: entity::Handle) {
+        let i = entity.get_index() as usize;
+
+        if i >= self.handles.len() {
+            self.handles.resize(i + 1, None);
+        }
+
+        self.handles[i] = Some(entity);
+    }
+
+    // Leaves the index alone if a newer generation holds it
+    pub(crate) fn release(&mut self, entity: entity::Handle) {
+        let i = entity.get_index() as usize;
+
+        if self.handles.get(i) == Some(&Some(entity)) {
+            self.handles[i] = None;
+        }
+    }
+
+    pub(crate) fn get(&self, index: u32) -> Option<entity::Handle> {
+        self.handles.get(index as usize).cloned().and_then(|handle| handle)
+    }
 }
 
 pub struct Container {
@@ -45,4 +111,108 @@ pub struct Container {
     pub softbodies: softbody::Manager,
     pub texts:      text::Manager,
     pub labels:     label::Manager,
+    pub controllers: controller::Manager,
+}
+
+impl Container {
+    /// Remove entity from every component manager and free its handle. \
+    /// Transform children are destroyed along with it; deparent them first
+    /// to keep them alive.
+    pub fn destroy(
+        &mut self,
+        entities: &mut entity::Manager,
+        entity: entity::Handle,
+    ) {
+        if !entities.check(entity) {
+            return; // NOOP
+        }
+
+        if self.transforms.registered(entity) {
+            for child in self.transforms.children(entity) {
+                self.destroy(entities, child);
+            }
+        }
+
+        self.transforms.unregister(entity);
+        self.cameras.unregister(entity);
+        self.lights.unregister(entity);
+        self.draws.unregister(entity);
+        self.softbodies.unregister(entity);
+        self.texts.unregister(entity);
+        self.labels.unregister(entity);
+        self.controllers.unregister(entity);
+
+        entities.remove(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use render;
+    use components::Component;
+
+    #[test]
+    fn destroy_body() {
+        let mut entities = entity::Manager::new(2);
+        let mut components = ::init_components(
+            render::Instances::new(0, &vec![], None),
+        );
+
+        let body = entities.add();
+        components.transforms.register(body);
+        components.softbodies.register(body);
+        components.softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(body);
+
+        let child = entities.add();
+        components.transforms.register(child);
+        components.transforms.parent(child, body);
+        components.lights.register(child);
+
+        components.destroy(&mut entities, body);
+
+        assert!(!entities.check(body));
+        assert!(!components.transforms.registered(body));
+        assert!(components.softbodies.count() == 0);
+
+        // Transform children go with their parent
+        assert!(!entities.check(child));
+        assert!(components.transforms.count() == 0);
+        assert!(components.lights.count() == 0);
+    }
+
+    #[test]
+    fn evict_stale() {
+        let mut entities = entity::Manager::new(1);
+        let mut components = ::init_components(
+            render::Instances::new(0, &vec![], None),
+        );
+
+        let stale = entities.add();
+        components.cameras.register(stale);
+        components.lights.register(stale);
+        components.controllers.register(stale);
+
+        // Removed without `destroy()`, so the managers still hold it
+        entities.remove(stale);
+        let entity = entities.add();
+        assert!(entity.get_index() == stale.get_index());
+
+        components.cameras.register(entity);
+        components.lights.register(entity);
+        components.controllers.register(entity);
+
+        assert!(components.cameras.count() == 1);
+        assert!(components.lights.count() == 1);
+        assert!(components.controllers.count() == 1);
+        assert!(!components.lights.registered(stale));
+        assert!(components.lights.find(entity.get_index()) == Some(entity));
+
+        // Unregistering the stale handle leaves the new one alone
+        components.lights.unregister(stale);
+        assert!(components.lights.find(entity.get_index()) == Some(entity));
+    }
 }
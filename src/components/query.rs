@@ -0,0 +1,100 @@
+use entity;
+
+/// Read access to a component manager by entity, for joins
+pub trait Query<'a> {
+    type Data;
+
+    /// Iterate over all entities registered with this manager
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a>;
+
+    /// Returns component data for the entity, if registered
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data>;
+}
+
+/// Iterate over every entity registered with both managers. \
+/// Iteration is driven by `a`, so pass the sparser manager first.
+pub fn join<'a, A, B>(
+    a: &'a A,
+    b: &'a B,
+) -> impl Iterator<Item = (entity::Handle, A::Data, B::Data)> + 'a
+where
+    A: Query<'a>,
+    B: Query<'a>,
+{
+    a.entities().filter_map(
+        move |entity| Some((entity, a.fetch(entity)?, b.fetch(entity)?))
+    )
+}
+
+/// Iterate over every entity registered with all three managers. \
+/// Iteration is driven by `a`, so pass the sparsest manager first.
+pub fn join3<'a, A, B, C>(
+    a: &'a A,
+    b: &'a B,
+    c: &'a C,
+) -> impl Iterator<Item = (entity::Handle, A::Data, B::Data, C::Data)> + 'a
+where
+    A: Query<'a>,
+    B: Query<'a>,
+    C: Query<'a>,
+{
+    a.entities().filter_map(
+        move |entity| Some((
+            entity,
+            a.fetch(entity)?,
+            b.fetch(entity)?,
+            c.fetch(entity)?,
+        ))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use components::Component;
+    use components::{light, softbody, transform};
+    use components::query::*;
+
+    #[test]
+    fn join_components() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = transform::Manager::new(3);
+        let mut softbodies = softbody::Manager::new(1, 1, 1);
+        let mut lights = light::Manager::new(1);
+
+        let body = entities.add();
+        transforms.register(body);
+        transforms.set_position(body, alg::Vec3::up());
+        softbodies.register(body);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(body);
+
+        // Softbody without a built instance is skipped
+        let unbuilt = entities.add();
+        transforms.register(unbuilt);
+        softbodies.register(unbuilt);
+
+        let lamp = entities.add();
+        transforms.register(lamp);
+        lights.register(lamp);
+
+        let joined = join(&softbodies, &transforms).collect::<Vec<_>>();
+        assert!(joined.len() == 1);
+
+        let (entity, _, transform) = joined[0];
+        assert!(entity == body);
+        assert!(transform.0 == transforms.get_position(body));
+
+        // No entity has both a softbody and a light
+        assert!(join(&softbodies, &lights).next().is_none());
+        assert!(join3(&lights, &transforms, &softbodies).next().is_none());
+
+        let lit = join3(&lights, &transforms, &transforms)
+            .map(|(entity, _, _, _)| entity)
+            .collect::<Vec<_>>();
+
+        assert!(lit == vec![lamp]);
+    }
+}
@@ -115,9 +115,9 @@ impl Particle {
         }
     }
 
-    pub fn init(&mut self, to: alg::Vec3, vel: alg::Vec3) {
+    pub fn init(&mut self, to: alg::Vec3, vel: alg::Vec3, fixed_dt: f32) {
         self.position = to;
-        self.last = to - vel * FIXED_DT;
+        self.last = to - vel * fixed_dt;
         self.displacement = alg::Vec3::zero();
     }
 }
@@ -444,6 +444,28 @@ impl<'a> JointBuilder<'a> {
     }
 }
 
+/// Joint parameters in the form accepted by `JointBuilder`
+#[derive(Clone, Copy)]
+pub struct JointDescription {
+    pub parent: entity::Handle,
+    pub child: entity::Handle,
+    pub x: (f32, f32), // Degrees
+    pub y: (f32, f32), // Degrees
+    pub z: (f32, f32), // Degrees
+    pub unlocked: bool,
+    pub fwd: alg::Vec3,
+    pub up: alg::Vec3,
+    pub offset: alg::Vec3,
+}
+
+/// How an instance was created, for serialization
+#[derive(Clone, PartialEq, Debug)]
+pub enum Source {
+    BoxLimb(alg::Vec3), // Box scale
+    Model(String), // Model name
+    Custom, // Raw particles (not serializable)
+}
+
 /* TODO: Refactor Instance data structure for memory performance
  * now that you have converged on how/where it is actually used.
  */
@@ -467,6 +489,7 @@ pub struct Instance {
 
     pub mass: f32,
     pub inv_pt_mass: f32, // Cached inverse mass per particle
+    fixed_dt: f32, // Copied from manager
     end_offset: f32, // Distance from center to simple endpoint
     start_indices: Vec<usize>, // Optional joint start highlight
     end_indices: Vec<usize>, // Optional joint end highlight
@@ -476,6 +499,8 @@ pub struct Instance {
     // Lower values produce springier meshes
     // A value of zero nullifies all rods in the instance
     pub rigidity: f32,
+
+    source: Source,
 }
 
 /// Source mesh reference structure.
@@ -500,6 +525,7 @@ impl Instance {
         rigidity: f32,
         initial_pos: alg::Vec3,
         initial_accel: alg::Vec3,
+        fixed_dt: f32,
         end_offset: f32,
         start_indices: &[usize],
         end_indices: &[usize],
@@ -554,7 +580,7 @@ impl Instance {
             match_shape,
 
             force: alg::Vec3::zero(),
-            accel_dt: initial_accel * FIXED_DT * FIXED_DT,
+            accel_dt: initial_accel * fixed_dt * fixed_dt,
 
             frame_position: alg::Vec3::zero(),
             frame_orientation_conjugate: alg::Quat::id(),
@@ -563,6 +589,7 @@ impl Instance {
 
             mass,
             inv_pt_mass: 1.0 / (mass / points_len as f32),
+            fixed_dt,
             model: Model {
                 positions: model,
                 com,
@@ -576,6 +603,7 @@ impl Instance {
             start_indices: start_indices.to_vec(),
             end_indices: end_indices.to_vec(),
             rigidity,
+            source: Source::Custom,
         }
     }
 
@@ -585,6 +613,7 @@ impl Instance {
         rigidity: f32,
         initial_pos: alg::Vec3,
         initial_accel: alg::Vec3,
+        fixed_dt: f32,
         end_offset: f32,
         start_indices: &[usize],
         end_indices: &[usize],
@@ -694,7 +723,7 @@ impl Instance {
             match_shape: true,
 
             force: alg::Vec3::zero(),
-            accel_dt: initial_accel * FIXED_DT * FIXED_DT,
+            accel_dt: initial_accel * fixed_dt * fixed_dt,
 
             frame_position: alg::Vec3::zero(),
             frame_orientation_conjugate: alg::Quat::id(),
@@ -703,6 +732,7 @@ impl Instance {
 
             mass,
             inv_pt_mass: 1.0 / (mass / vertices_len as f32),
+            fixed_dt,
             end_offset,
             start_indices,
             end_indices,
@@ -753,11 +783,33 @@ impl Instance {
     #[inline]
     fn update_cache(&mut self, gravity: alg::Vec3) {
         self.accel_dt = (self.force * self.inv_pt_mass + gravity)
-            * FIXED_DT * FIXED_DT;
+            * self.fixed_dt * self.fixed_dt;
+    }
+
+    // Change timestep while preserving particle velocities
+    fn set_fixed_dt(&mut self, fixed_dt: f32, gravity: alg::Vec3) {
+        let ratio = fixed_dt / self.fixed_dt;
+
+        for particle in &mut self.particles {
+            particle.last = particle.position
+                - (particle.position - particle.last) * ratio;
+        }
+
+        self.fixed_dt = fixed_dt;
+        self.update_cache(gravity);
     }
 
     /* General instance methods */
 
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Distance from center to simple endpoint
+    pub fn end_offset(&self) -> f32 {
+        self.end_offset
+    }
+
     pub fn center(&self) -> alg::Vec3 {
         self.particles.iter().fold(
             alg::Vec3::zero(),
@@ -770,14 +822,14 @@ impl Instance {
         let new = self.particles.iter().fold(
             alg::Vec3::zero(),
             |sum, particle| sum + particle.displacement,
-        ) / (self.particles.len() as f32 * FIXED_DT);
+        ) / (self.particles.len() as f32 * self.fixed_dt);
 
        new.lerp(self.frame_vel, INTEGRAL_SMOOTH_BIAS)
     }
 
     fn compute_accel(&mut self, new_vel: alg::Vec3) -> alg::Vec3 {
         let diff = new_vel - self.frame_vel;
-        let new = diff / FIXED_DT;
+        let new = diff / self.fixed_dt;
         new.lerp(self.frame_accel, INTEGRAL_SMOOTH_BIAS)
     }
 
@@ -798,7 +850,7 @@ impl Instance {
             alg::Vec3::zero(),
             |sum, particle| {
                 let r = particle.position - center; // m
-                let v = particle.displacement / FIXED_DT - velocity; // m/s
+                let v = particle.displacement / self.fixed_dt - velocity; // m/s
                 let r_mag = r.mag();
 
                 sum + r.cross(v)      // m^2/s
@@ -1051,7 +1103,7 @@ impl<'a> InstanceBuilder<'a> {
 
         /* Box limb instance */
 
-        let instance = if let Some(scale) = self.scale {
+        let mut instance = if let Some(scale) = self.scale {
             let scale = scale * 0.5;
 
             debug_assert!(self.model.is_none());
@@ -1117,6 +1169,7 @@ impl<'a> InstanceBuilder<'a> {
                 self.rigidity,
                 self.initial_pos,
                 initial_accel,
+                self.manager.fixed_dt,
                 self.end_offset.unwrap_or(0.5),
                 &[0, 1, 2, 3], // Start indices
                 &[4, 5, 6, 7], // End indices
@@ -1136,6 +1189,7 @@ impl<'a> InstanceBuilder<'a> {
                 self.rigidity,
                 self.initial_pos,
                 initial_accel,
+                self.manager.fixed_dt,
                 self.end_offset.unwrap_or(0.0),
                 self.start_indices.unwrap_or(&[]),
                 self.end_indices.unwrap_or(&[]),
@@ -1158,12 +1212,19 @@ impl<'a> InstanceBuilder<'a> {
                 self.rigidity,
                 self.initial_pos,
                 initial_accel,
+                self.manager.fixed_dt,
                 self.end_offset.unwrap_or(0.0),
                 self.start_indices.unwrap_or(&[]),
                 self.end_indices.unwrap_or(&[]),
             )
         };
 
+        instance.source = if let Some(scale) = self.scale {
+            Source::BoxLimb(scale)
+        } else if let Some(model) = self.model {
+            Source::Model(model.name.clone())
+        } else { Source::Custom };
+
         // Register with manager
         self.manager.add_instance(instance, entity);
     }
@@ -1179,6 +1240,7 @@ pub struct Manager {
     planes: Vec<alg::Plane>,
 
     pub iterations: usize,
+    fixed_dt: f32,
     gravity: alg::Vec3,
     drag: f32,
     friction: f32,
@@ -1187,6 +1249,8 @@ pub struct Manager {
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
         let i = entity.get_index() as usize;
 
         // Resize array to fit new entity
@@ -1200,15 +1264,38 @@ impl components::Component for Manager {
             break;
         }
 
+        self.evict(entity);
+
         self.handles[i] = Some(entity);
+        self.instances[i] = None;
         self.count += 1;
 
         debug_assert!(self.handles.len() == self.instances.len());
     }
 
+    fn unregister(&mut self, entity: entity::Handle) {
+        use components::Component;
+        if !self.registered(entity) { return; }
+
+        let i = entity.get_index() as usize;
+
+        // Remove joints to and from this instance
+        self.joints.remove(&i);
+
+        for joints in self.joints.values_mut() {
+            joints.retain(|joint| joint.child != i);
+        }
+
+        self.joints.retain(|_, joints| !joints.is_empty());
+
+        self.handles[i] = None;
+        self.instances[i] = None;
+        self.count -= 1;
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         let i = entity.get_index() as usize;
-        i < self.instances.len() && self.handles[i].is_some()
+        i < self.instances.len() && self.handles[i] == Some(entity)
     }
 
     fn count(&self) -> usize {
@@ -1216,6 +1303,31 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Softbody" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.handles.get(index as usize).cloned().and_then(|handle| handle)
+    }
+}
+
+// Only yields entities with a built instance
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = &'a Instance;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(
+            self.handles.iter().zip(&self.instances)
+                .filter_map(|(handle, instance)| {
+                    if instance.is_some() { *handle } else { None }
+                })
+        )
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        use components::Component;
+        if !self.registered(entity) { return None; }
+
+        self.instances[entity.get_index() as usize].as_ref()
+    }
 }
 
 impl Manager {
@@ -1236,6 +1348,7 @@ impl Manager {
             joints: joint_map,
             planes: Vec::with_capacity(plane_hint),
             iterations: MNGR_DEFAULT_ITER,
+            fixed_dt: FIXED_DT,
             gravity: alg::Vec3::new(0., -9.8, 0.), // Default gravity
             drag: MNGR_DEFAULT_DRAG,
             friction: MNGR_DEFAULT_FRICTION,
@@ -1272,7 +1385,7 @@ impl Manager {
                 |i| i.particles.iter()
                     .map(
                         move |p|
-                        (1. / i.inv_pt_mass, p.displacement.mag() / FIXED_DT)
+                        (1. / i.inv_pt_mass, p.displacement.mag() / i.fixed_dt)
                     )
             ).flatten()
                 .fold(0., |acc, p| acc + 0.5 * p.0 * p.1 * p.1); // KE
@@ -1575,6 +1688,50 @@ impl Manager {
         planes.iter().for_each(|plane| self.add_plane(*plane));
     }
 
+    /// Add a joint from its description (see `joints()`)
+    pub fn add_joint_description(&mut self, joint: JointDescription) {
+        let radians = |(min, max): (f32, f32)| Range {
+            min: min.to_radians(),
+            max: max.to_radians(),
+        };
+
+        self.add_joint(
+            joint.parent,
+            joint.child,
+            alg::Quat::from_vecs(joint.fwd, joint.up),
+            joint.offset,
+            (radians(joint.x), radians(joint.y), radians(joint.z)),
+            joint.unlocked,
+        );
+    }
+
+    pub fn planes(&self) -> &[alg::Plane] {
+        &self.planes
+    }
+
+    /// Returns all joints, in no particular order
+    pub fn joints(&self) -> Vec<JointDescription> {
+        let degrees = |range: Range| (
+            range.min.to_degrees(),
+            range.max.to_degrees(),
+        );
+
+        self.joints.iter()
+            .flat_map(|(parent, joints)| joints.iter().map(
+                move |joint| (*parent, joint)
+            )).map(|(parent, joint)| JointDescription {
+                parent: self.handles[parent].unwrap(),
+                child: self.handles[joint.child].unwrap(),
+                x: degrees(joint.x_limit),
+                y: degrees(joint.y_limit),
+                z: degrees(joint.z_limit),
+                unlocked: joint.unlocked,
+                fwd: joint.transform * alg::Vec3::fwd(),
+                up: joint.transform * alg::Vec3::up(),
+                offset: joint.offset,
+            }).collect()
+    }
+
     /// Set gravity for all instances. \
     /// Heavier call than `set_gravity_raw(...)`, \
     /// but will force-update all instances.
@@ -1594,6 +1751,23 @@ impl Manager {
         self.gravity = gravity;
     }
 
+    /// Seconds of simulated time per fixed step \
+    /// Existing instances keep their velocities.
+    pub fn set_fixed_dt(&mut self, fixed_dt: f32) {
+        debug_assert!(fixed_dt > 0.0);
+        self.fixed_dt = fixed_dt;
+
+        for i in 0..self.instances.len() {
+            if let Some(ref mut instance) = self.instances[i] {
+                instance.set_fixed_dt(fixed_dt, self.gravity);
+            }
+        }
+    }
+
+    pub fn fixed_dt(&self) -> f32 {
+        self.fixed_dt
+    }
+
     /// Range 0 - 1; 0 = no drag; 1 = nothing moves
     pub fn set_drag(&mut self, drag: f32) {
         self.drag = drag;
@@ -1658,10 +1832,10 @@ impl Manager {
         }
 
         // Solve abstracted constraints first
-        // Note: "true" delta time is FIXED_DT / ITERATIONS
+        // Note: "true" delta time is fixed_dt / ITERATIONS
         for _ in 0..self.iterations {
             // External constraints
-            game.iterate(FIXED_DT, self.iterations, self);
+            game.iterate(self.fixed_dt, self.iterations, self);
 
             // Joint constraints
             self.solve_joints();
@@ -1756,7 +1930,7 @@ impl Manager {
             transforms.set_raw(i, center, orientation, alg::Vec3::one());
 
             for particle in &mut instance.particles {
-                // Meters per FIXED_DT
+                // Meters per fixed_dt
                 particle.displacement = particle.position - particle.last;
             }
 
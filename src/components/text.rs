@@ -51,17 +51,28 @@ impl<'a> TextBuilder<'a> {
 
 pub struct Manager {
     instances: fnv::FnvHashMap<entity::Handle, render::Text>,
+    slots: components::Slots,
     pub instance_data: Vec<render::FontUBO>,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
+        self.evict(entity);
+        self.slots.claim(entity);
+
         self.instances.insert(
             entity,
             render::Text::empty_3d_instance(),
         );
     }
 
+    fn unregister(&mut self, entity: entity::Handle) {
+        self.instances.remove(&entity);
+        self.slots.release(entity);
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         self.instances.contains_key(&entity)
     }
@@ -71,6 +82,22 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Text" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.slots.get(index)
+    }
+}
+
+impl<'a> components::query::Query<'a> for Manager {
+    type Data = &'a render::Text;
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(self.instances.keys().cloned())
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        self.instances.get(&entity)
+    }
 }
 
 impl Manager {
@@ -80,6 +107,7 @@ impl Manager {
                 hint,
                 Default::default(),
             ),
+            slots: components::Slots::new(hint),
             instance_data: Vec::with_capacity(hint),
         }
     }
@@ -1,3 +1,4 @@
+use std;
 use alg;
 use entity;
 use components;
@@ -39,9 +40,21 @@ pub struct Transform {
           scale: alg::Vec3,
     local_scale: alg::Vec3,
 
+    /* Worldspace pose before the last fixed step, for render
+     * interpolation. Only differs from the current pose for transforms
+     * driven by physics (`set_raw()`) and their descendants.
+     */
+
+    last_position: alg::Vec3,
+    last_orientation: alg::Quat,
+    stepped: u32, // Fixed step of the last snapshot, see `begin_step()`
+
     parent: Option<usize>,
     children: Vec<usize>,
     cached_transform: alg::Mat4, // Cached world transform
+
+    dirty: bool, // Local transform changed since the last `update()`
+    interpolate: bool, // Keep the previous pose on the next update
 }
 
 impl Transform {
@@ -54,9 +67,16 @@ impl Transform {
                   scale: alg::Vec3::one(),
             local_scale: alg::Vec3::one(),
 
+            last_position: alg::Vec3::zero(),
+            last_orientation: alg::Quat::id(),
+            stepped: 0,
+
             parent: None,
             children: Vec::with_capacity(child_hint),
             cached_transform: alg::Mat4::id(),
+
+            dirty: false,
+            interpolate: false,
         }
     }
 
@@ -69,60 +89,147 @@ impl Transform {
         debug_assert!(self.parent.is_some());
         let parent = manager.instances[self.parent.unwrap()].as_ref().unwrap();
 
-        // Check for non-uniform scale at runtime
-        #[cfg(debug_assertions)] {
-            let parent_scale = parent.cached_transform.to_scale();
-            if !parent_scale.is_uniform() {
-                eprintln!(
-                    "Warning: Non-uniform scale is not supported \
-                    in transform hierarchy"
-                );
-            }
-        }
-
         // Rebuild cached transform for this instance
-        let transform = parent.cached_transform
-            * alg::Mat4::transform(
-                self.local_position,
-                self.local_orientation,
-                self.local_scale,
-            );
+        let transform = parent.cached_transform * self.local_mat();
 
         /* Assign transform data */
 
-        let scale = transform.to_scale();
+        let (position, orientation, scale) = transform.decompose();
 
+        self.position = position;
+        self.orientation = orientation;
         self.scale = scale;
-        self.orientation = transform.to_rotation_raw(scale).to_quat();
-        self.position = transform.to_position();
         self.cached_transform = transform;
+
+        // Only physics updates are interpolated; other children follow
+        // the interpolation of their parent
+        if self.interpolate {
+            self.interpolate = false;
+        } else {
+            let last = parent.last_mat() * self.local_mat();
+            let (position, orientation, _) = last.decompose();
+
+            self.last_position = position;
+            self.last_orientation = orientation;
+        }
+    }
+
+    fn local_mat(&self) -> alg::Mat4 {
+        alg::Mat4::transform(
+            self.local_position,
+            self.local_orientation,
+            self.local_scale,
+        )
+    }
+
+    /// World transform before the last fixed step (see `last_position`)
+    fn last_mat(&self) -> alg::Mat4 {
+        self.interpolated_mat(0.0)
+    }
+
+    fn interpolated_mat(&self, alpha: f32) -> alg::Mat4 {
+        interpolate_mat(
+            (self.last_position, self.last_orientation),
+            (self.position, self.orientation),
+            self.cached_transform,
+            alpha,
+        )
+    }
+
+    fn interpolated(&self, alpha: f32) -> (alg::Vec3, alg::Quat) {
+        interpolate(
+            (self.last_position, self.last_orientation),
+            (self.position, self.orientation),
+            alpha,
+        )
+    }
+
+    /// Discard the previous pose so that the transform is not interpolated
+    /// (e.g. when teleported)
+    fn snap(&mut self) {
+        self.last_position = self.position;
+        self.last_orientation = self.orientation;
     }
 
-    /// Recursively call `update_cached()` on all children
+    /// Recursively call `update_cached()` on all children,
+    /// clearing their dirty flags
     unsafe fn update_children(&self, manager: &mut Manager) {
         for child_index in &self.children {
             #[allow(unused_unsafe)]
             let child = get_mut_instance_raw!(manager, *child_index);
 
             child.update_cached(manager);
+            child.dirty = false;
             child.update_children(manager);
         }
     }
 }
 
+fn interpolate(
+    last: (alg::Vec3, alg::Quat),
+    current: (alg::Vec3, alg::Quat),
+    alpha: f32,
+) -> (alg::Vec3, alg::Quat) {
+    // Take the shortest path
+    let orientation = if last.1.dot(current.1) < 0.0 {
+        current.1.neg()
+    } else { current.1 };
+
+    (
+        last.0.lerp(current.0, alpha),
+        last.1.nlerp(orientation, alpha),
+    )
+}
+
+/// Blend between the previous and current pose, applying the rigid
+/// offset to the full matrix so that shear is kept
+fn interpolate_mat(
+    last: (alg::Vec3, alg::Quat),
+    current: (alg::Vec3, alg::Quat),
+    transform: alg::Mat4,
+    alpha: f32,
+) -> alg::Mat4 {
+    let (position, orientation) = interpolate(last, current, alpha);
+
+    let offset = orientation.to_mat()
+        * current.1.conjugate().to_mat();
+
+    alg::Mat4::translation_vec(position)
+        * (offset * alg::Mat4::translation_vec(-current.0))
+        * transform
+}
+
+/* Setters only write the local transform (and the world transform of
+ * hierarchy roots) and mark the instance dirty; worldspace data for the
+ * rest of the hierarchy is propagated once per frame by `update()`.
+ * Getters resolve dirty chains on the fly, so reads are never stale.
+ *
+ * The cached world transform is a full affine matrix, so non-uniform
+ * scale anywhere in the hierarchy is exact. Worldspace position,
+ * orientation and scale are decomposed from it; if the matrix is sheared
+ * (a rotated child of a non-uniformly scaled parent), the shear is only
+ * available through `get_mat()`.
+ */
+
 // Data layout assumes that almost all entities will have this component
 pub struct Manager {
+    handles: Vec<Option<entity::Handle>>,
     instances: Vec<Option<Transform>>,
+    dirty: Vec<usize>, // Indices marked dirty since the last update
     count: usize,
+    step: u32, // Current fixed step, never zero
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        use components::Component;
+
         let i = entity.get_index() as usize;
 
         // Resize array to fit new entity
         loop {
             if i >= self.instances.len() {
+                self.handles.push(None);
                 self.instances.push(None);
                 continue;
             }
@@ -130,13 +237,33 @@ impl components::Component for Manager {
             break;
         }
 
+        self.evict(entity);
+
+        self.handles[i] = Some(entity);
         self.instances[i] = Some(Transform::blank(0));
         self.count += 1;
     }
 
+    // Children are deparented, keeping their worldspace transforms
+    fn unregister(&mut self, entity: entity::Handle) {
+        use components::Component;
+        if !self.registered(entity) { return; }
+
+        for child in self.children(entity) {
+            self.deparent(child);
+        }
+
+        self.deparent(entity);
+
+        let i = entity.get_index() as usize;
+        self.handles[i] = None;
+        self.instances[i] = None;
+        self.count -= 1;
+    }
+
     fn registered(&self, entity: entity::Handle) -> bool {
         let i = entity.get_index() as usize;
-        i < self.instances.len() && self.instances[i].is_some()
+        i < self.instances.len() && self.handles[i] == Some(entity)
     }
 
     fn count(&self) -> usize {
@@ -144,18 +271,191 @@ impl components::Component for Manager {
     }
 
     #[cfg(debug_assertions)] fn debug_name(&self) -> &str { "Transform" }
+
+    fn find(&self, index: u32) -> Option<entity::Handle> {
+        self.handles.get(index as usize).cloned().and_then(|handle| handle)
+    }
+}
+
+impl<'a> components::query::Query<'a> for Manager {
+    // Position, rotation, scale (see `get()`)
+    type Data = (alg::Vec3, alg::Quat, alg::Vec3);
+
+    fn entities(&'a self) -> Box<dyn Iterator<Item = entity::Handle> + 'a> {
+        Box::new(self.handles.iter().filter_map(|handle| *handle))
+    }
+
+    fn fetch(&'a self, entity: entity::Handle) -> Option<Self::Data> {
+        use components::Component;
+        if !self.registered(entity) { return None; }
+
+        Some(self.world(entity.get_index() as usize))
+    }
 }
 
 impl Manager {
     pub fn new(hint: usize) -> Manager {
         Manager {
+            handles: Vec::with_capacity(hint),
             instances: Vec::with_capacity(hint),
+            dirty: Vec::new(),
             count: 0,
+            step: 1,
+        }
+    }
+
+    /// Start a new fixed step: the next `set_raw()` on each transform
+    /// keeps its current pose for interpolation. \
+    /// Called by the engine before every fixed step.
+    pub(crate) fn begin_step(&mut self) {
+        self.step = self.step.wrapping_add(1).max(1);
+    }
+
+    /// Propagate worldspace transforms through every dirty hierarchy. \
+    /// Called by the engine once per frame, before rendering, and by
+    /// `parent()` and `deparent()`; getters resolve dirty instances on
+    /// their own in between. Each instance is updated at most once per call.
+    pub fn update(&mut self) {
+        if self.dirty.is_empty() { return; }
+
+        let dirty = std::mem::replace(&mut self.dirty, Vec::new());
+
+        for index in &dirty {
+            // Skip freed instances and hierarchies that were already updated
+            match self.instances[*index] {
+                Some(ref transform) if transform.dirty => (),
+                _ => continue,
+            }
+
+            // Start from the topmost dirty ancestor, so that parents
+            // are always updated before their children
+            let mut top = *index;
+            let mut current = *index;
+
+            while let Some(parent) = self.instances[current]
+                .as_ref().unwrap().parent
+            {
+                current = parent;
+
+                if self.instances[current].as_ref().unwrap().dirty {
+                    top = current;
+                }
+            }
+
+            let transform = get_mut_instance_raw!(self, top);
+
+            if transform.parent.is_some() {
+                transform.update_cached(self);
+            }
+
+            transform.dirty = false;
+            unsafe { transform.update_children(self); }
+        }
+
+        // Reuse allocation
+        self.dirty = dirty;
+        self.dirty.clear();
+    }
+
+    // Flag instance for the next update, if it is part of a hierarchy
+    fn mark_dirty(&mut self, index: usize) {
+        let transform = get_mut_instance_raw!(self, index);
+
+        if transform.dirty { return; }
+        if transform.parent.is_none() && transform.children.is_empty() {
+            return;
+        }
+
+        transform.dirty = true;
+        self.dirty.push(index);
+    }
+
+    /// Returns the up-to-date world transform of an instance if it or
+    /// one of its ancestors is dirty (i.e. the cached data may be stale)
+    fn resolve(&self, index: usize) -> Option<alg::Mat4> {
+        let transform = self.instances[index].as_ref().unwrap();
+
+        // Roots are always up to date
+        let parent_index = match transform.parent {
+            Some(parent_index) => parent_index,
+            None if transform.dirty => return Some(transform.cached_transform),
+            None => return None,
+        };
+
+        match self.resolve(parent_index) {
+            Some(parent) => Some(parent * transform.local_mat()),
+            None if transform.dirty => {
+                let parent = self.instances[parent_index].as_ref().unwrap();
+                Some(parent.cached_transform * transform.local_mat())
+            },
+            None => None,
         }
     }
 
+    // Full worldspace transform
+    fn world_mat(&self, index: usize) -> alg::Mat4 {
+        if !self.dirty.is_empty() {
+            if let Some(transform) = self.resolve(index) {
+                return transform;
+            }
+        }
+
+        self.instances[index].as_ref().unwrap().cached_transform
+    }
+
+    // Worldspace position, orientation, scale
+    fn world(&self, index: usize) -> (alg::Vec3, alg::Quat, alg::Vec3) {
+        if !self.dirty.is_empty() {
+            if let Some(transform) = self.resolve(index) {
+                return transform.decompose();
+            }
+        }
+
+        let transform = self.instances[index].as_ref().unwrap();
+
+        (
+            transform.position,
+            transform.orientation,
+            transform.scale,
+        )
+    }
+
+    /// World transform before the last fixed step, as `update()` would
+    /// propagate it: children that are not driven by physics follow the
+    /// previous pose of their parent
+    fn last_world_mat(&self, index: usize) -> alg::Mat4 {
+        let transform = self.instances[index].as_ref().unwrap();
+
+        match transform.parent {
+            Some(parent) if !transform.interpolate => {
+                self.last_world_mat(parent) * transform.local_mat()
+            },
+
+            _ => transform.last_mat(),
+        }
+    }
+
+    /// Returns the up-to-date current and previous world transforms of
+    /// an instance whose hierarchy has not been propagated yet. \
+    /// Roots always keep both poses, so they are never resolved.
+    fn resolve_interpolated(
+        &self,
+        index: usize,
+    ) -> Option<(alg::Mat4, alg::Mat4)> {
+        if self.dirty.is_empty() { return None; }
+        if self.instances[index].as_ref().unwrap().parent.is_none() {
+            return None;
+        }
+
+        self.resolve(index)
+            .map(|current| (current, self.last_world_mat(index)))
+    }
+
     /// Set transform parent of `entity` to `parent` and update the child's
-    /// local transform
+    /// local transform. \
+    /// If the parent is non-uniformly scaled and the child is rotated
+    /// relative to it, the shear can't be kept in the local transform
+    /// and the child's worldspace transform will change.
     pub fn parent(&mut self, entity: entity::Handle, parent: entity::Handle) {
         debug_validate_entity!(self, entity); // Child
         debug_validate_entity!(self, parent);
@@ -166,6 +466,9 @@ impl Manager {
             }
         }
 
+        // Worldspace data must be current to compute the local transform
+        self.update();
+
         let transform_index = entity.get_index() as usize;
         let transform = get_mut_instance_raw!(self, transform_index);
 
@@ -173,15 +476,20 @@ impl Manager {
         let parent_transform = get_mut_instance_raw!(self, parent_index);
 
         #[cfg(debug_assertions)] {
-            if transform.children.contains(&parent_index) {
+            if self.is_ancestor(transform_index, parent_index) {
                 panic!(
-                    "Attemped to parent entity {} to its child {}",
+                    "Attemped to parent entity {} to its descendant {}",
                     entity,
                     parent,
                 );
             }
         }
 
+        // Remove from the previous parent, if any
+        if transform.parent != Some(parent_index) {
+            self.detach(transform_index);
+        }
+
         transform.parent = Some(parent_index);
 
         if !parent_transform.children.contains(&transform_index) {
@@ -190,19 +498,16 @@ impl Manager {
 
         /* Update local transform relative to the new parent */
 
-        // Note: parent scale cannot be non-uniform
-        let inverse_scale = 1.0 / parent_transform.scale.x;
-        transform.local_scale = transform.scale * inverse_scale;
+        let local = parent_transform.cached_transform.inverse_affine()
+            * transform.cached_transform;
 
-        let inverse_orient = parent_transform.orientation.conjugate();
-        transform.local_orientation = transform.orientation * inverse_orient;
+        let (position, orientation, scale) = local.decompose();
 
-        transform.local_position = inverse_orient
-            * (transform.position - parent_transform.position)
-            * inverse_scale;
+        transform.local_position = position;
+        transform.local_orientation = orientation;
+        transform.local_scale = scale;
 
-        transform.update_cached(self);
-        unsafe { transform.update_children(self); }
+        self.mark_dirty(transform_index);
     }
 
     /// Set transform parent of `entity` to `parent` without updating the
@@ -228,53 +533,154 @@ impl Manager {
         let parent_transform = get_mut_instance_raw!(self, parent_index);
 
         #[cfg(debug_assertions)] {
-            if transform.children.contains(&parent_index) {
+            if self.is_ancestor(transform_index, parent_index) {
                 panic!(
-                    "Attemped to parent entity {} to its child {}",
+                    "Attemped to parent entity {} to its descendant {}",
                     entity,
                     parent,
                 );
             }
         }
 
+        // Remove from the previous parent, if any
+        if transform.parent != Some(parent_index) {
+            self.detach(transform_index);
+        }
+
         transform.parent = Some(parent_index);
 
         if !parent_transform.children.contains(&transform_index) {
             parent_transform.children.push(transform_index);
         }
 
-        transform.update_cached(self);
-        unsafe { transform.update_children(self); }
+        transform.interpolate = false;
+        self.mark_dirty(transform_index);
     }
 
-    /// Clear parent of entity
+    /// Clear parent of entity, keeping its worldspace transform \
+    /// Roots can't be sheared, so any shear from a non-uniformly scaled
+    /// parent is discarded.
     pub fn deparent(&mut self, entity: entity::Handle) {
         debug_validate_entity!(self, entity); // Child
 
+        // Worldspace data becomes the new local transform
+        self.update();
+
         let child_index = entity.get_index() as usize;
-        let child = get_mut_instance_raw!(self, child_index);
+        if !self.detach(child_index) { return; } // NOOP
 
-        if let Some(parent_index) = child.parent {
-            let parent = get_mut_instance_raw!(self, parent_index);
+        // Update local transform to reflect new parent
+        let child = get_mut_instance_raw!(self, child_index);
+        child.local_position = child.position;
+        child.local_orientation = child.orientation;
+        child.local_scale = child.scale;
+        child.cached_transform = child.local_mat();
 
-            // Get index of child
-            let i = parent.children.iter().enumerate()
-                .find(|(_, j)| **j == child_index)
-                .unwrap().0;
+        // Children follow the (unsheared) transform
+        self.mark_dirty(child_index);
+    }
 
-            parent.children.remove(i);
-        } else {
-            return // NOOP
+    /// Set or clear the transform parent of `entity`, keeping its
+    /// worldspace transform
+    pub fn reparent(
+        &mut self,
+        entity: entity::Handle,
+        parent: Option<entity::Handle>,
+    ) {
+        match parent {
+            Some(parent) => self.parent(entity, parent),
+            None => self.deparent(entity),
         }
+    }
+
+    // Remove instance from its parent's children, if any,
+    // without updating its local transform
+    fn detach(&mut self, index: usize) -> bool {
+        let child = get_mut_instance_raw!(self, index);
+
+        let parent_index = match child.parent {
+            Some(parent_index) => parent_index,
+            None => return false,
+        };
+
+        let parent = get_mut_instance_raw!(self, parent_index);
 
+        // Get index of child
+        let i = parent.children.iter().enumerate()
+            .find(|(_, j)| **j == index)
+            .unwrap().0;
+
+        parent.children.remove(i);
         child.parent = None;
 
-        // Update local transform to reflect new parent
-        child.local_position = child.position;
-        child.local_orientation = child.orientation;
-        child.local_scale = child.scale;
+        true
+    }
+
+    // Returns true if `ancestor` is `index` or one of its parents
+    #[cfg(debug_assertions)]
+    fn is_ancestor(&self, ancestor: usize, index: usize) -> bool {
+        let mut current = Some(index);
+
+        while let Some(i) = current {
+            if i == ancestor { return true; }
+            current = self.instances[i].as_ref().unwrap().parent;
+        }
+
+        false
+    }
+
+    /// Returns handle of the transform parent of `entity`, if any
+    pub fn get_parent(&self, entity: entity::Handle) -> Option<entity::Handle> {
+        let transform = get_instance!(self, entity);
+        transform.parent.map(|i| self.handles[i].unwrap())
+    }
+
+    /// Returns handles of the direct children of `entity`
+    pub fn children(&self, entity: entity::Handle) -> Vec<entity::Handle> {
+        let transform = get_instance!(self, entity);
+
+        transform.children.iter()
+            .map(|i| self.handles[*i].unwrap())
+            .collect()
+    }
+
+    /// Returns handles of all descendants of `entity` (not including
+    /// itself), depth-first: each child is followed by its own descendants
+    pub fn descendants(&self, entity: entity::Handle) -> Vec<entity::Handle> {
+        let transform = get_instance!(self, entity);
+
+        let mut result = Vec::new();
+        let mut stack = transform.children.iter().rev()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        while let Some(i) = stack.pop() {
+            result.push(self.handles[i].unwrap());
+
+            let children = &self.instances[i].as_ref().unwrap().children;
+            stack.extend(children.iter().rev());
+        }
+
+        result
+    }
+
+    /// Returns handle of the topmost ancestor of `entity`
+    /// (or `entity` itself, if it has no parent)
+    pub fn get_root(&self, entity: entity::Handle) -> entity::Handle {
+        let mut i = {
+            let transform = get_instance!(self, entity);
+
+            match transform.parent {
+                Some(parent) => parent,
+                None => return entity,
+            }
+        };
 
-        unsafe { child.update_children(self); }
+        while let Some(parent) = self.instances[i].as_ref().unwrap().parent {
+            i = parent;
+        }
+
+        self.handles[i].unwrap()
     }
 
     /// Returns tuple of position, rotation, scale \
@@ -284,39 +690,146 @@ impl Manager {
         alg::Quat,
         alg::Vec3,
     ) {
-        let transform = get_instance!(self, entity);
-
-        (
-            transform.position,
-            transform.orientation,
-            transform.scale,
-        )
+        debug_validate_entity!(self, entity);
+        self.world(entity.get_index() as usize)
     }
 
-    /// Returns transform data as alg::Mat4
+    /// Returns transform data as alg::Mat4 \
+    /// Unlike `get()`, this includes any shear from non-uniform scale.
     pub fn get_mat(&self, entity: entity::Handle) -> alg::Mat4 {
+        debug_validate_entity!(self, entity);
+        self.world_mat(entity.get_index() as usize)
+    }
+
+    pub fn get_position(&self, entity: entity::Handle) -> alg::Vec3 {
+        self.get(entity).0
+    }
+
+    pub fn get_orientation(&self, entity: entity::Handle) -> alg::Quat {
+        self.get(entity).1
+    }
+
+    pub fn get_scale(&self, entity: entity::Handle) -> alg::Vec3 {
+        self.get(entity).2
+    }
+
+    /// Position relative to the transform parent
+    /// (same as the worldspace position for roots)
+    pub fn get_local_position(&self, entity: entity::Handle) -> alg::Vec3 {
         let transform = get_instance!(self, entity);
+        transform.local_position
+    }
 
-        alg::Mat4::transform(
-            transform.position,
-            transform.orientation,
-            transform.scale,
-        )
+    /// Orientation relative to the transform parent
+    pub fn get_local_orientation(&self, entity: entity::Handle) -> alg::Quat {
+        let transform = get_instance!(self, entity);
+        transform.local_orientation
     }
 
-    pub fn get_position(&self, entity: entity::Handle) -> alg::Vec3 {
+    /// Scale relative to the transform parent
+    pub fn get_local_scale(&self, entity: entity::Handle) -> alg::Vec3 {
         let transform = get_instance!(self, entity);
-        transform.position
+        transform.local_scale
     }
 
-    pub fn get_orientation(&self, entity: entity::Handle) -> alg::Quat {
+    /// Convert point from the entity's local space to worldspace
+    pub fn transform_point(
+        &self,
+        entity: entity::Handle,
+        point: alg::Vec3,
+    ) -> alg::Vec3 {
+        self.get_mat(entity) * point
+    }
+
+    /// Convert direction from the entity's local space to worldspace \
+    /// Only rotation is applied; the result has the same length.
+    pub fn transform_direction(
+        &self,
+        entity: entity::Handle,
+        direction: alg::Vec3,
+    ) -> alg::Vec3 {
+        self.get_orientation(entity) * direction
+    }
+
+    /// Convert point from worldspace to the entity's local space
+    pub fn inverse_transform_point(
+        &self,
+        entity: entity::Handle,
+        point: alg::Vec3,
+    ) -> alg::Vec3 {
+        self.get_mat(entity).inverse_affine() * point
+    }
+
+    /// Convert direction from worldspace to the entity's local space \
+    /// Only rotation is applied; the result has the same length.
+    pub fn inverse_transform_direction(
+        &self,
+        entity: entity::Handle,
+        direction: alg::Vec3,
+    ) -> alg::Vec3 {
+        self.get_orientation(entity).conjugate() * direction
+    }
+
+    /// Returns tuple of position, rotation, scale, blended between the pose
+    /// before the last fixed step and the current pose. \
+    /// `alpha` is the fraction of a fixed step elapsed since (see
+    /// `Metadata::alpha`).
+    pub fn get_interpolated(
+        &self,
+        entity: entity::Handle,
+        alpha: f32,
+    ) -> (
+        alg::Vec3,
+        alg::Quat,
+        alg::Vec3,
+    ) {
         let transform = get_instance!(self, entity);
-        transform.orientation
+        let i = entity.get_index() as usize;
+
+        // Not yet propagated; blend against the previous pose of the
+        // hierarchy instead
+        if let Some((current, last)) = self.resolve_interpolated(i) {
+            let (position, orientation, scale) = current.decompose();
+            let (last_position, last_orientation, _) = last.decompose();
+
+            let (position, orientation) = interpolate(
+                (last_position, last_orientation),
+                (position, orientation),
+                alpha,
+            );
+
+            return (position, orientation, scale);
+        }
+
+        let (position, orientation) = transform.interpolated(alpha);
+        (position, orientation, transform.scale)
     }
 
-    pub fn get_scale(&self, entity: entity::Handle) -> alg::Vec3 {
+    /// Same as `get_interpolated()`, as a matrix that keeps any shear
+    /// from non-uniform scale
+    pub fn get_mat_interpolated(
+        &self,
+        entity: entity::Handle,
+        alpha: f32,
+    ) -> alg::Mat4 {
         let transform = get_instance!(self, entity);
-        transform.scale
+        let i = entity.get_index() as usize;
+
+        // Not yet propagated; blend against the previous pose of the
+        // hierarchy instead
+        if let Some((current, last)) = self.resolve_interpolated(i) {
+            let (position, orientation, _) = current.decompose();
+            let (last_position, last_orientation, _) = last.decompose();
+
+            return interpolate_mat(
+                (last_position, last_orientation),
+                (position, orientation),
+                current,
+                alpha,
+            );
+        }
+
+        transform.interpolated_mat(alpha)
     }
 
     /// Set transform data \
@@ -331,6 +844,11 @@ impl Manager {
         debug_validate_entity!(self, entity);
         let i = entity.get_index() as usize;
         self.set_raw(i, position, orientation, scale);
+
+        // Only physics updates are interpolated
+        let transform = get_mut_instance_raw!(self, i);
+        transform.interpolate = false;
+        transform.snap();
     }
 
     /// Set transform position
@@ -343,22 +861,17 @@ impl Manager {
         let i = entity.get_index() as usize;
         let transform = get_mut_instance_raw!(self, i);
         transform.local_position = position;
+        transform.interpolate = false;
 
-        /* Set worldspace transform data */
-
-        // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
-        }
-
-        // No parent (chain root)--just set data
-        else {
+        // No parent (chain root)--just set worldspace data
+        if transform.parent.is_none() {
             transform.position = position;
             transform.cached_transform.set_translation(position);
+            transform.snap();
         }
 
-        // Update children transforms
-        unsafe { transform.update_children(self); }
+        // Update rest of chain on the next update
+        self.mark_dirty(i);
     }
 
     /// Set transform orientation
@@ -371,26 +884,22 @@ impl Manager {
         let i = entity.get_index() as usize;
         let transform = get_mut_instance_raw!(self, i);
         transform.local_orientation = orientation;
+        transform.interpolate = false;
 
-        /* Set worldspace transform data */
-
-        // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
-        }
-
-        // No parent (chain root)--just set data
-        else {
+        // No parent (chain root)--just set worldspace data
+        if transform.parent.is_none() {
             transform.orientation = orientation;
             transform.cached_transform = alg::Mat4::transform(
                 transform.position,
                 transform.orientation,
                 transform.scale,
             );
+
+            transform.snap();
         }
 
-        // Update children transforms
-        unsafe { transform.update_children(self); }
+        // Update rest of chain on the next update
+        self.mark_dirty(i);
     }
 
     /// Set transform scale
@@ -403,37 +912,59 @@ impl Manager {
         let i = entity.get_index() as usize;
         let transform = get_mut_instance_raw!(self, i);
 
-        // Check for non-uniform scale at assignment-time
-        #[cfg(debug_assertions)] {
-            if !transform.children.is_empty() && !scale.is_uniform() {
-                panic!(
-                    "Non-uniform scale is not supported \
-                    in transform hierarchy"
-                );
-            }
-        }
-
         transform.local_scale = scale;
+        transform.interpolate = false;
 
-        /* Set worldspace transform data */
-
-        // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
-        }
-
-        // No parent (chain root)--just set data
-        else {
+        // No parent (chain root)--just set worldspace data
+        if transform.parent.is_none() {
             transform.scale = scale;
             transform.cached_transform = alg::Mat4::transform(
                 transform.position,
                 transform.orientation,
                 transform.scale,
             );
+
+            transform.snap();
         }
 
-        // Update children transforms
-        unsafe { transform.update_children(self); }
+        // Update rest of chain on the next update
+        self.mark_dirty(i);
+    }
+
+    /// Set worldspace position, regardless of the transform parent
+    pub fn set_world_position(
+        &mut self,
+        entity: entity::Handle,
+        position: alg::Vec3,
+    ) {
+        debug_validate_entity!(self, entity);
+        let i = entity.get_index() as usize;
+
+        let local = match self.instances[i].as_ref().unwrap().parent {
+            Some(parent) => self.world_mat(parent).inverse_affine() * position,
+            None => position,
+        };
+
+        self.set_position(entity, local);
+    }
+
+    /// Set worldspace orientation, regardless of the transform parent \
+    /// Under a non-uniformly scaled parent the result may be sheared
+    /// (see `get_mat()`).
+    pub fn set_world_orientation(
+        &mut self,
+        entity: entity::Handle,
+        orientation: alg::Quat,
+    ) {
+        debug_validate_entity!(self, entity);
+        let i = entity.get_index() as usize;
+
+        let local = match self.instances[i].as_ref().unwrap().parent {
+            Some(parent) => self.world(parent).1.conjugate() * orientation,
+            None => orientation,
+        };
+
+        self.set_orientation(entity, local);
     }
 
     /* "Unsafe" methods for components with similar data layouts.
@@ -441,7 +972,7 @@ impl Manager {
      * for performance purposes.
      */
 
-    /// Set transform data and update chain
+    /// Set transform data and mark chain dirty
     pub(super) fn set_raw(
         &mut self,
         index: usize,
@@ -449,31 +980,23 @@ impl Manager {
         orientation: alg::Quat,
         scale: alg::Vec3,
     ) {
+        let step = self.step;
         let transform = get_mut_instance_raw!(self, index);
 
-        // Check for non-uniform scale at assignment-time
-        #[cfg(debug_assertions)] {
-            if !transform.children.is_empty() && !scale.is_uniform() {
-                panic!(
-                    "Non-uniform scale is not supported \
-                    in transform hierarchy"
-                );
-            }
+        // Keep the pose from before this fixed step for interpolation,
+        // even if the transform is written more than once during it
+        if transform.stepped != step {
+            transform.stepped = step;
+            transform.last_position = transform.position;
+            transform.last_orientation = transform.orientation;
         }
 
         transform.local_position = position;
         transform.local_orientation = orientation;
         transform.local_scale = scale;
 
-        /* Set worldspace transform data */
-
-        // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
-        }
-
-        // No parent (chain root)--just set data
-        else {
+        // No parent (chain root)--just set worldspace data
+        if transform.parent.is_none() {
             transform.position = position;
             transform.orientation = orientation;
             transform.scale = scale;
@@ -485,7 +1008,297 @@ impl Manager {
             );
         }
 
-        // Update children transforms
-        unsafe { transform.update_children(self); }
+        // Child worldspace data is set on the next update
+        else {
+            transform.interpolate = true;
+        }
+
+        self.mark_dirty(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use components::Component;
+    use components::transform::*;
+
+    #[test]
+    fn propagate_dirty() {
+        let mut entities = entity::Manager::new(4);
+        let mut transforms = Manager::new(4);
+
+        // Chain of four, each one unit right of its parent
+        let chain = (0..4).map(|_| {
+            let entity = entities.add();
+            transforms.register(entity);
+            transforms.set_position(entity, alg::Vec3::right());
+            entity
+        }).collect::<Vec<_>>();
+
+        for i in 1..chain.len() {
+            transforms.parent_raw(chain[i], chain[i - 1]);
+        }
+
+        transforms.update();
+
+        let tip = chain[3];
+        let position = transforms.get_position(tip);
+        assert!(position.dist(alg::Vec3::right() * 4.0) < 1e-5);
+
+        // Reads before the update are resolved through the dirty chain
+        transforms.set_position(chain[0], alg::Vec3::zero());
+        transforms.set_orientation(
+            chain[1],
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0),
+        );
+
+        let resolved = transforms.get_position(tip);
+        assert!(resolved.dist(alg::Vec3::right() * 3.0) > 1e-3);
+
+        transforms.update();
+        assert!(transforms.get_position(tip).dist(resolved) < 1e-5);
+        assert!(transforms.dirty.is_empty());
+    }
+
+    #[test]
+    fn non_uniform_parent() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let container = entities.add();
+        transforms.register(container);
+        transforms.set_scale(container, alg::Vec3::new(1.0, 4.0, 1.0));
+
+        let child = entities.add();
+        transforms.register(child);
+        transforms.set_position(child, alg::Vec3::new(1.0, 2.0, 3.0));
+
+        // Worldspace transform is kept when parenting
+        let before = transforms.get_mat(child);
+        transforms.parent(child, container);
+        transforms.update();
+
+        let point = alg::Vec3::new(0.5, 0.5, 0.5);
+        let after = transforms.get_mat(child);
+        assert!((after * point).dist(before * point) < 1e-4);
+
+        // Rotating the child under the stretched parent shears it
+        transforms.set_orientation(
+            child,
+            alg::Quat::axis_angle(alg::Vec3::fwd(), 1.0),
+        );
+
+        let expected = transforms.get_mat(container)
+            * alg::Mat4::transform(
+                alg::Vec3::new(1.0, 0.5, 3.0),
+                alg::Quat::axis_angle(alg::Vec3::fwd(), 1.0),
+                alg::Vec3::new(1.0, 0.25, 1.0),
+            );
+
+        transforms.update();
+        let actual = transforms.get_mat(child);
+        assert!((actual * point).dist(expected * point) < 1e-4);
+
+        // Orientation accessor still returns a valid rotation
+        let orientation = transforms.get_orientation(child);
+        assert!((orientation.to_mat().det() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn local_world_conversion() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        transforms.register(parent);
+        transforms.set(
+            parent,
+            alg::Vec3::new(1.0, 2.0, 3.0),
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0),
+            alg::Vec3::one() * 2.0,
+        );
+
+        let child = entities.add();
+        transforms.register(child);
+        transforms.parent_raw(child, parent);
+        transforms.set_position(child, alg::Vec3::right());
+
+        let point = transforms.transform_point(child, alg::Vec3::zero());
+        assert!(point.dist(transforms.get_position(child)) < 1e-5);
+
+        let local = transforms.inverse_transform_point(parent, point);
+        assert!(local.dist(transforms.get_local_position(child)) < 1e-5);
+
+        let fwd = alg::Vec3::fwd();
+        let direction = transforms.transform_direction(parent, fwd);
+        let back = transforms.inverse_transform_direction(parent, direction);
+        assert!((direction.mag() - 1.0).abs() < 1e-5);
+        assert!(back.dist(fwd) < 1e-5);
+
+        // World setters account for the parent
+        let target = alg::Vec3::new(-4.0, 5.0, 0.5);
+        transforms.set_world_position(child, target);
+        assert!(transforms.get_position(child).dist(target) < 1e-4);
+
+        let orientation = alg::Quat::axis_angle(alg::Vec3::right(), 0.5);
+        transforms.set_world_orientation(child, orientation);
+        transforms.update();
+
+        let forward = transforms.transform_direction(child, alg::Vec3::fwd());
+        assert!(forward.dist(orientation * alg::Vec3::fwd()) < 1e-4);
+    }
+
+    #[test]
+    fn traverse_hierarchy() {
+        let mut entities = entity::Manager::new(5);
+        let mut transforms = Manager::new(5);
+
+        let nodes = (0..5).map(|_| {
+            let entity = entities.add();
+            transforms.register(entity);
+            entity
+        }).collect::<Vec<_>>();
+
+        // 0 -> (1 -> (2, 3), 4)
+        transforms.parent(nodes[1], nodes[0]);
+        transforms.parent(nodes[2], nodes[1]);
+        transforms.parent(nodes[3], nodes[1]);
+        transforms.parent(nodes[4], nodes[0]);
+
+        let descendants = transforms.descendants(nodes[0]);
+        assert!(descendants == vec![nodes[1], nodes[2], nodes[3], nodes[4]]);
+        assert!(transforms.get_root(nodes[3]) == nodes[0]);
+        assert!(transforms.get_root(nodes[0]) == nodes[0]);
+
+        // Move the subtree under 4, keeping its worldspace pose
+        transforms.set_position(nodes[4], alg::Vec3::new(0.0, 5.0, 0.0));
+        transforms.set_position(nodes[2], alg::Vec3::right());
+        let before = transforms.get_position(nodes[2]);
+
+        transforms.reparent(nodes[1], Some(nodes[4]));
+        assert!(transforms.get_parent(nodes[1]) == Some(nodes[4]));
+        assert!(transforms.children(nodes[0]) == vec![nodes[4]]);
+        assert!(transforms.get_position(nodes[2]).dist(before) < 1e-5);
+
+        transforms.reparent(nodes[1], None);
+        assert!(transforms.get_root(nodes[2]) == nodes[1]);
+        assert!(transforms.descendants(nodes[4]).is_empty());
+        assert!(transforms.get_position(nodes[2]).dist(before) < 1e-5);
+    }
+
+    #[test]
+    fn interpolate_dirty() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let root = entities.add();
+        transforms.register(root);
+
+        let child = entities.add();
+        transforms.register(child);
+        transforms.parent_raw(child, root);
+        transforms.set_position(child, alg::Vec3::right());
+        transforms.update();
+
+        // Physics step moves the root two units up
+        let i = root.get_index() as usize;
+        let orientation = alg::Quat::axis_angle(alg::Vec3::up(), 1.0);
+        transforms.set_raw(
+            i,
+            alg::Vec3::up() * 2.0,
+            orientation,
+            alg::Vec3::one(),
+        );
+
+        // Dirty roots and their children are still blended
+        let position = transforms.get_interpolated(root, 0.5).0;
+        assert!(position.dist(alg::Vec3::up()) < 1e-5);
+
+        let before = transforms.get_interpolated(child, 0.5);
+        let before_mat = transforms.get_mat_interpolated(child, 0.5);
+
+        transforms.update();
+
+        let after = transforms.get_interpolated(child, 0.5);
+        let after_mat = transforms.get_mat_interpolated(child, 0.5);
+        assert!(before.0.dist(after.0) < 1e-5);
+        assert!(before.1.dot(after.1).abs() > 1.0 - 1e-5);
+
+        let point = alg::Vec3::new(0.5, 0.5, 0.5);
+        assert!((before_mat * point).dist(after_mat * point) < 1e-4);
+    }
+
+    #[test]
+    fn interpolate_repeated_writes() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.update();
+
+        let i = entity.get_index() as usize;
+        let write = |transforms: &mut Manager, height: f32| {
+            transforms.set_raw(
+                i,
+                alg::Vec3::up() * height,
+                alg::Quat::id(),
+                alg::Vec3::one(),
+            );
+        };
+
+        // Both writes blend from the pose before the step
+        transforms.begin_step();
+        write(&mut transforms, 1.0);
+        write(&mut transforms, 2.0);
+
+        let position = transforms.get_interpolated(entity, 0.5).0;
+        assert!(position.dist(alg::Vec3::up()) < 1e-5);
+
+        // The next step blends from the last write
+        transforms.begin_step();
+        write(&mut transforms, 4.0);
+
+        let position = transforms.get_interpolated(entity, 0.5).0;
+        assert!(position.dist(alg::Vec3::up() * 3.0) < 1e-5);
+    }
+
+    #[test]
+    fn reuse_slot() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let stale = entities.add();
+        transforms.register(stale);
+        transforms.set_position(stale, alg::Vec3::up());
+
+        // Entity is destroyed without unregistering its transform
+        entities.remove(stale);
+        let entity = entities.add();
+        assert!(entity.get_index() == stale.get_index());
+
+        transforms.register(entity);
+        assert!(transforms.count() == 1);
+        assert!(!transforms.registered(stale));
+        assert!(transforms.get_position(entity) == alg::Vec3::zero());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn stale_handle() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let stale = entities.add();
+        transforms.register(stale);
+        transforms.unregister(stale);
+        entities.remove(stale);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.get_position(stale);
     }
 }
@@ -1,72 +1,231 @@
 extern crate ini;
 use std;
 
+use error::{Error, Result};
+
 lazy_static! {
-    pub static ref ENGINE_CONFIG: ini::Ini =
-        load_config("config.ini");
+    // Engine configuration loaded from `config.ini`, or the load error
+    // message; accessed through `engine_config()`
+    static ref ENGINE_CONFIG: std::result::Result<ini::Ini, String> =
+        load_config("config.ini").map_err(|e| e.to_string());
 }
 
-pub fn load_config(filename: &str) -> ini::Ini {
-    match ini::Ini::load_from_file(filename) {
-        Ok(result) => result,
-        Err(e) => panic!("{}", e.to_string()),
+/// Returns the engine configuration, loaded once on first access
+pub fn engine_config() -> Result<&'static ini::Ini> {
+    match *ENGINE_CONFIG {
+        Ok(ref config) => Ok(config),
+        Err(ref message) => Err(Error::Config(message.clone())),
     }
 }
 
+pub fn load_config(filename: &str) -> Result<ini::Ini> {
+    ini::Ini::load_from_file(filename).map_err(
+        |e| Error::Config(format!("Could not load \"{}\": {}", filename, e))
+    )
+}
+
 pub fn load_section_setting<T: std::str::FromStr> (
     config: &ini::Ini,
     section: &str,
     setting: &str,
-) -> T
+) -> Result<T>
 where <T as std::str::FromStr>::Err: std::error::Error {
-    let settings = config.section(Some(section))
-        .unwrap_or_else(
-        || panic!(
-            "Failed to load section \"{}\"",
-            section,
-        )
-    );
+    let settings = load_section(config, section)?;
 
     let raw = settings.get(setting)
-        .unwrap_or_else(
-        || panic!(
-            "Failed to load setting \"{}\" in section \"{}\"",
+        .ok_or_else(
+            || Error::Config(format!(
+                "Failed to load setting \"{}\" in section \"{}\"",
+                setting,
+                section,
+            ))
+        )?;
+
+    parse_section_setting(raw, section, setting)
+}
+
+/// Same as `load_section_setting()`, returning `default` if the setting
+/// (or its section) is missing. \
+/// Values that are present but invalid are still an error.
+pub fn load_section_setting_or<T: std::str::FromStr> (
+    config: &ini::Ini,
+    section: &str,
+    setting: &str,
+    default: T,
+) -> Result<T>
+where <T as std::str::FromStr>::Err: std::error::Error {
+    let raw = config.section(Some(section))
+        .and_then(|settings| settings.get(setting));
+
+    match raw {
+        Some(raw) => parse_section_setting(raw, section, setting),
+        None => Ok(default),
+    }
+}
+
+/// Same as `load_section_setting()`, rejecting values that are not
+/// positive and finite (e.g. timesteps and time scales)
+pub fn load_positive_setting(
+    config: &ini::Ini,
+    section: &str,
+    setting: &str,
+) -> Result<f32> {
+    let value = load_section_setting::<f32>(config, section, setting)?;
+    check_positive(value, section, setting)
+}
+
+/// Same as `load_positive_setting()`, returning `default` if the setting
+/// (or its section) is missing
+pub fn load_positive_setting_or(
+    config: &ini::Ini,
+    section: &str,
+    setting: &str,
+    default: f32,
+) -> Result<f32> {
+    let value = load_section_setting_or(config, section, setting, default)?;
+    check_positive(value, section, setting)
+}
+
+fn parse_section_setting<T: std::str::FromStr> (
+    raw: &str,
+    section: &str,
+    setting: &str,
+) -> Result<T>
+where <T as std::str::FromStr>::Err: std::error::Error {
+    raw.parse::<T>().map_err(
+        |e| Error::Config(format!(
+            "Invalid value \"{}\" for setting \"{}\" in section \"{}\": {}",
+            raw,
             setting,
             section,
-        )
-    );
+            e,
+        ))
+    )
+}
 
-    match raw.parse::<T>() {
-        Ok(result) => result,
-        Err(e) => panic!("{}", e.to_string()),
+fn check_positive(value: f32, section: &str, setting: &str) -> Result<f32> {
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(Error::Config(format!(
+            "Setting \"{}\" in section \"{}\" must be positive, not {}",
+            setting,
+            section,
+            value,
+        )))
     }
 }
 
 pub fn load_section<'a>(
     config: &'a ini::Ini,
     section: &str,
-) -> &'a ini::ini::Properties {
+) -> Result<&'a ini::ini::Properties> {
     config.section(Some(section))
-        .unwrap_or_else(
-            || panic!(
+        .ok_or_else(
+            || Error::Config(format!(
                 "Failed to load section \"{}\"",
                 section,
-            )
+            ))
         )
 }
 
 pub fn load_setting<T: std::str::FromStr> (
     section: &ini::ini::Properties,
     setting: &str,
-) -> T
+) -> Result<T>
 where <T as std::str::FromStr>::Err: std::error::Error {
     let raw = section.get(setting)
-        .unwrap_or_else(
-            || panic!("Failed to load setting \"{}\"", setting)
+        .ok_or_else(
+            || Error::Config(format!("Failed to load setting \"{}\"", setting))
+        )?;
+
+    raw.parse::<T>().map_err(
+        |e| Error::Config(format!(
+            "Invalid value \"{}\" for setting \"{}\": {}",
+            raw,
+            setting,
+            e,
+        ))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use config::*;
+
+    #[test]
+    fn missing_file() {
+        let result = load_config("missing.ini");
+
+        if let Err(Error::Config(message)) = result {
+            assert!(message.contains("missing.ini"));
+        } else { panic!("Expected config error") }
+    }
+
+    #[test]
+    fn invalid_setting() {
+        let config = ini::Ini::load_from_str(
+            "[settings]\nfixed_dt=fast\n"
+        ).unwrap();
+
+        let result = load_section_setting::<f32>(
+            &config,
+            "settings",
+            "fixed_dt",
+        );
+
+        if let Err(Error::Config(message)) = result {
+            assert!(message.contains("fast"));
+        } else { panic!("Expected config error") }
+
+        // Missing section
+        let result = load_section_setting::<f32>(&config, "window", "width");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn non_positive_setting() {
+        for value in &["0", "-0.01", "inf", "NaN"] {
+            let config = ini::Ini::load_from_str(
+                &format!("[settings]\nfixed_dt={}\n", value)
+            ).unwrap();
+
+            let result = load_positive_setting(&config, "settings", "fixed_dt");
+            assert!(result.is_err());
+        }
+
+        let config = ini::Ini::load_from_str(
+            "[settings]\nfixed_dt=0.01\n"
+        ).unwrap();
+
+        let result = load_positive_setting(&config, "settings", "fixed_dt");
+        assert!(result.unwrap() == 0.01);
+    }
+
+    #[test]
+    fn default_setting() {
+        let config = ini::Ini::load_from_str(
+            "[settings]\nfps=144\nfixed_dt=\n"
+        ).unwrap();
+
+        // Missing settings and sections fall back to the default
+        let result = load_section_setting_or(&config, "settings", "fov", 60);
+        assert!(result.unwrap() == 60);
+
+        let result = load_section_setting_or(&config, "window", "width", 0);
+        assert!(result.unwrap() == 0);
+
+        let result = load_section_setting_or(&config, "settings", "fps", 60);
+        assert!(result.unwrap() == 144);
+
+        // Present but invalid values are not replaced
+        let result = load_positive_setting_or(
+            &config,
+            "settings",
+            "fixed_dt",
+            0.01,
         );
 
-    match raw.parse::<T>() {
-        Ok(result) => result,
-        Err(e) => panic!("{}", e.to_string()),
+        assert!(result.is_err());
     }
 }
@@ -1,43 +1,64 @@
 extern crate fnv;
 use std;
 
+/// Entity identifier: an index into component storage, paired with a
+/// generation that is bumped every time the index is freed. \
+/// A handle kept past `remove()` no longer matches its index and is
+/// rejected by `Manager::check()` and component validation.
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
 pub struct Handle {
-    _value: u32,
+    index: u32,
+    generation: u32,
 }
 
 impl Handle {
-    fn new(index: u32) -> Handle {
+    fn new(index: u32, generation: u32) -> Handle {
         Handle {
-            _value: index,
+            index,
+            generation,
         }
     }
 
     pub fn get_index(self) -> u32 {
-        self._value
+        self.index
+    }
+
+    pub fn get_generation(self) -> u32 {
+        self.generation
     }
 }
 
 impl std::fmt::Display for Handle {
     fn fmt(&self, out: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(out, "{}", self._value)
+        write!(out, "{}:{}", self.index, self.generation)
     }
 }
 
 pub struct Manager {
-    data: fnv::FnvHashSet<Handle>,
-    index: u32,
+    generations: Vec<u32>, // Current generation of each index
+    free: std::collections::VecDeque<u32>, // Removed indices, oldest first
     count: u32,
+
+    /* Optional metadata */
+
+    names: fnv::FnvHashMap<Handle, String>,
+    named: fnv::FnvHashMap<String, Handle>, // Reverse lookup
+    tags: fnv::FnvHashMap<String, fnv::FnvHashSet<Handle>>,
+
+    recorded: Option<Vec<Handle>>, // Entities added while recording
 }
 
 impl Manager {
     pub fn new(hint: usize) -> Manager {
-        let data = fnv::FnvHashSet::with_capacity_and_hasher(
-            hint,
-            Default::default(),
-        );
-
-        Manager { data, index: 0, count: 0 }
+        Manager {
+            generations: Vec::with_capacity(hint),
+            free: std::collections::VecDeque::new(),
+            count: 0,
+            names: fnv::FnvHashMap::default(),
+            named: fnv::FnvHashMap::default(),
+            tags: fnv::FnvHashMap::default(),
+            recorded: None,
+        }
     }
 
     pub fn add(&mut self) -> Handle {
@@ -45,40 +66,204 @@ impl Manager {
             panic!("Out of space for new entities!");
         }
 
-        loop {
-            // If an entity already exists, skip
-            if self.check(Handle::new(self.index)) {
-                self.index = self.index.wrapping_add(1);
-                continue;
-            }
-
-            break;
-        }
+        // Reuse the least recently freed index, so that generations
+        // wrap around as slowly as possible
+        let index = match self.free.pop_front() {
+            Some(index) => index,
+            None => {
+                self.generations.push(0);
+                (self.generations.len() - 1) as u32
+            },
+        };
 
-        // Add new entity
-        let handle = Handle::new(self.index);
-        self.data.insert(handle);
         self.count += 1;
+        let handle = Handle::new(index, self.generations[index as usize]);
 
-        // Offset for next time
-        self.index = self.index.wrapping_add(1);
+        if let Some(ref mut recorded) = self.recorded {
+            recorded.push(handle);
+        }
 
         handle
     }
 
     pub fn check(&self, handle: Handle) -> bool {
-        self.data.contains(&handle)
+        let i = handle.index as usize;
+        i < self.generations.len() && self.generations[i] == handle.generation
     }
 
-    // Idempotent--but access a handle after remove() at your own risk!
+    // Idempotent--stale handles are rejected after remove()
     pub fn remove(&mut self, handle: Handle) {
-        if self.data.remove(&handle) {
-            // Decrement counter
-            self.count -= 1;
+        if !self.check(handle) { return; }
+
+        self.clear_name(handle);
+
+        for tagged in self.tags.values_mut() {
+            tagged.remove(&handle);
         }
+
+        // Invalidate outstanding handles to this index
+        let i = handle.index as usize;
+        self.generations[i] = self.generations[i].wrapping_add(1);
+        self.free.push_back(handle.index);
+
+        // Decrement counter
+        self.count -= 1;
     }
 
     pub fn count(&self) -> usize {
-        self.data.len()
+        self.count as usize
+    }
+
+    /// Iterate over all live entities, in index order
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Handle> + 'a {
+        let free = self.free.iter().cloned()
+            .collect::<fnv::FnvHashSet<_>>();
+
+        self.generations.iter().enumerate()
+            .map(|(i, generation)| Handle::new(i as u32, *generation))
+            .filter(move |handle| !free.contains(&handle.index))
+    }
+
+    /// Assign a unique name to the entity, replacing its previous name. \
+    /// If another entity has the same name, it loses it.
+    pub fn set_name(&mut self, handle: Handle, name: &str) {
+        debug_assert!(self.check(handle));
+
+        if let Some(previous) = self.named.get(name).cloned() {
+            #[cfg(debug_assertions)] {
+                if previous != handle {
+                    eprintln!(
+                        "Warning: Name \"{}\" moved from entity {} to {}",
+                        name,
+                        previous,
+                        handle,
+                    );
+                }
+            }
+
+            self.names.remove(&previous);
+        }
+
+        self.clear_name(handle);
+        self.names.insert(handle, name.to_string());
+        self.named.insert(name.to_string(), handle);
+    }
+
+    /// Remove the entity's name, if it has one
+    pub fn clear_name(&mut self, handle: Handle) {
+        if let Some(name) = self.names.remove(&handle) {
+            self.named.remove(&name);
+        }
+    }
+
+    pub fn get_name(&self, handle: Handle) -> Option<&str> {
+        self.names.get(&handle).map(|name| name.as_str())
+    }
+
+    /// Returns the entity with the given name
+    pub fn find(&self, name: &str) -> Option<Handle> {
+        self.named.get(name).cloned()
+    }
+
+    /// Add tag to entity (idempotent)
+    pub fn add_tag(&mut self, handle: Handle, tag: &str) {
+        debug_assert!(self.check(handle));
+
+        self.tags.entry(tag.to_string())
+            .or_insert_with(fnv::FnvHashSet::default)
+            .insert(handle);
+    }
+
+    /// Remove tag from entity (idempotent)
+    pub fn remove_tag(&mut self, handle: Handle, tag: &str) {
+        if let Some(tagged) = self.tags.get_mut(tag) {
+            tagged.remove(&handle);
+        }
+    }
+
+    pub fn has_tag(&self, handle: Handle, tag: &str) -> bool {
+        self.tags.get(tag)
+            .map_or(false, |tagged| tagged.contains(&handle))
+    }
+
+    /// Start collecting the handles of new entities, discarding any
+    /// that were collected but not yet returned by `end_recording()`
+    pub(crate) fn begin_recording(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    /// Stop collecting new entities, returning the handles of those added
+    /// since `begin_recording()` (including any that were removed since)
+    pub(crate) fn end_recording(&mut self) -> Vec<Handle> {
+        self.recorded.take().unwrap_or_default()
+    }
+
+    /// Iterate over all entities with the given tag (in arbitrary order)
+    pub fn tagged<'a>(
+        &'a self,
+        tag: &str,
+    ) -> impl Iterator<Item = Handle> + 'a {
+        self.tags.get(tag).into_iter()
+            .flat_map(|tagged| tagged.iter().cloned())
+    }
+
+    /// Iterate over the tags of an entity (linear in the number of tags)
+    pub fn tags<'a>(
+        &'a self,
+        handle: Handle,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.tags.iter()
+            .filter(move |(_, tagged)| tagged.contains(&handle))
+            .map(|(tag, _)| tag.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::*;
+
+    #[test]
+    fn stale_handle() {
+        let mut entities = Manager::new(4);
+
+        let first = entities.add();
+        entities.remove(first);
+        assert!(!entities.check(first));
+
+        // Index is reused with a new generation
+        let second = entities.add();
+        assert!(second.get_index() == first.get_index());
+        assert!(second != first);
+        assert!(entities.check(second));
+        assert!(!entities.check(first));
+
+        // Removing a stale handle does not affect the live one
+        entities.remove(first);
+        assert!(entities.check(second));
+        assert!(entities.count() == 1);
+    }
+
+    #[test]
+    fn names_and_tags() {
+        let mut entities = Manager::new(4);
+
+        let player = entities.add();
+        let enemy = entities.add();
+
+        entities.set_name(player, "player");
+        entities.add_tag(enemy, "enemy");
+        entities.add_tag(enemy, "hostile");
+
+        assert!(entities.find("player") == Some(player));
+        assert!(entities.get_name(player) == Some("player"));
+        assert!(entities.tagged("enemy").collect::<Vec<_>>() == vec![enemy]);
+        assert!(entities.tags(enemy).count() == 2);
+
+        // Metadata is cleared on removal
+        entities.remove(enemy);
+        assert!(entities.tagged("enemy").next().is_none());
+
+        entities.remove(player);
+        assert!(entities.find("player").is_none());
     }
 }
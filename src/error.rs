@@ -0,0 +1,54 @@
+extern crate voodoo as vd;
+
+use std;
+
+/// Engine error type, returned from startup and other fallible operations
+/// that the game may want to report or recover from
+#[derive(Debug)]
+pub enum Error {
+    /// Missing or malformed engine configuration
+    Config(String),
+    /// Font data or texture could not be loaded
+    Font(String),
+    /// Model file could not be loaded
+    Model(String),
+    /// Window could not be created
+    Window(String),
+    /// Gamepad context could not be created
+    Gamepad(String),
+    /// Vulkan error (e.g. no suitable device)
+    Render(vd::Error),
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, out: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Config(message) => write!(out, "Config error: {}", message),
+            Error::Font(message) => write!(out, "Font error: {}", message),
+            Error::Model(message) => write!(out, "Model error: {}", message),
+            Error::Window(message) => write!(out, "Window error: {}", message),
+            Error::Gamepad(message) => {
+                write!(out, "Gamepad error: {}", message)
+            },
+            Error::Render(e) => write!(out, "Render error: {}", e),
+            Error::Io(e) => write!(out, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error { }
+
+impl From<vd::Error> for Error {
+    fn from(e: vd::Error) -> Error {
+        Error::Render(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
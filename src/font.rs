@@ -7,12 +7,18 @@ use std::io::BufReader;
 use std::io::BufRead;
 use std::fs::File;
 
+use error::{Error, Result};
+
 macro_rules! get_float_from_pair {
     ($( $x:expr ),* ) => {
         {$(
             get_value_from_pair(
-                $x.next().expect("Found None value instead of Some")
-            ).parse::<f32>().unwrap()
+                $x.next().ok_or_else(
+                    || Error::Font("Unexpected end of line".into())
+                )?
+            )?.parse::<f32>().map_err(
+                |e| Error::Font(format!("Could not parse value: {}", e))
+            )?
         )*}
     };
 }
@@ -50,12 +56,13 @@ pub struct CommonFont {
     pub size: f32,
 }
 
-fn get_value_from_pair<'a> (pair: &'a str) -> &'a str {
-    let val:Vec<&str> = pair.split('=').collect();
-    val[1]
+fn get_value_from_pair<'a> (pair: &'a str) -> Result<&'a str> {
+    pair.split('=').nth(1).ok_or_else(
+        || Error::Font(format!("Expected key=value pair, found \"{}\"", pair))
+    )
 }
 
-fn parse_bmchar<'a>(full_str: &'a str) -> Bmchar {
+fn parse_bmchar<'a>(full_str: &'a str) -> Result<Bmchar> {
     let mut iter = full_str.split_whitespace();
     iter.next(); // Skip
 
@@ -70,26 +77,32 @@ fn parse_bmchar<'a>(full_str: &'a str) -> Bmchar {
     let page = get_float_from_pair!(iter) as i32;
     //let chnl = get_float_from_pair!(iter);
 
-    Bmchar {
-        id,
-        x,
-        y,
-        width,
-        height,
-        xoffset,
-        yoffset,
-        xadvance,
-        page,
-    }
+    Ok(
+        Bmchar {
+            id,
+            x,
+            y,
+            width,
+            height,
+            xoffset,
+            yoffset,
+            xadvance,
+            page,
+        }
+    )
 }
 
 impl Data {
     pub fn new(
         font_data_path: &str
-    ) -> Data {
-        let file_handle = File::open(font_data_path).unwrap_or_else(
-            |err| panic!("Could not load font data file: \"{}\"", err)
-        );
+    ) -> Result<Data> {
+        let file_handle = File::open(font_data_path).map_err(
+            |err| Error::Font(format!(
+                "Could not load font data file \"{}\": {}",
+                font_data_path,
+                err,
+            ))
+        )?;
 
         let mut pixels = Vec::new();
         let file = BufReader::new(&file_handle);
@@ -107,17 +120,20 @@ impl Data {
         let mut font_texture_name: String;
 
         for line in file.lines() {
-            let line_string = line.unwrap_or_else(
-                |err| panic!("Could not unwrap line: \"{}\"", err)
-            );
+            let line_string = line.map_err(
+                |err| Error::Font(format!("Could not read line: {}", err))
+            )?;
 
             let clone = line_string.clone();
             let mut iter = clone.split_whitespace();
 
             match iter.next() {
                 Some("info") => {
-                    let pair = iter.next().expect("Could not find font face");
-                    font_face = get_value_from_pair(pair).replace("\"", "");
+                    let pair = iter.next().ok_or_else(
+                        || Error::Font("Could not find font face".into())
+                    )?;
+
+                    font_face = get_value_from_pair(pair)?.replace("\"", "");
                     println!("Reading font \"{}\"", font_face);
                     size = get_float_from_pair!(iter);
                     continue;
@@ -134,12 +150,12 @@ impl Data {
                 Some("page") => {
                     iter.next(); // Skip
 
-                    let pair = iter.next().expect(
-                        "Found None value instead of Some"
-                    );
+                    let pair = iter.next().ok_or_else(
+                        || Error::Font("Could not find font texture".into())
+                    )?;
 
                     let mut path_vec: Vec<&str> = font_data_path.split("/").collect();
-                    font_texture_name = get_value_from_pair(pair).replace("\"", "");
+                    font_texture_name = get_value_from_pair(pair)?.replace("\"", "");
 
                     let last_idx = path_vec.len() - 1;
                     path_vec[last_idx] = &font_texture_name;
@@ -147,26 +163,38 @@ impl Data {
                     let path = path_vec.join("/");
 
                     let decoder = png::Decoder::new(
-                        File::open(path).unwrap_or_else(
-                            |err| panic!("Could not find file: \"{}\"", err)
-                        )
+                        File::open(&path).map_err(
+                            |err| Error::Font(format!(
+                                "Could not find file \"{}\": {}",
+                                path,
+                                err,
+                            ))
+                        )?
                     );
 
                     let (info, mut reader) = decoder.read_info()
-                        .unwrap_or_else(
-                            |err| panic!("Could not decode path: \"{}\"", err)
-                        );
+                        .map_err(
+                            |err| Error::Font(format!(
+                                "Could not decode \"{}\": {}",
+                                path,
+                                err,
+                            ))
+                        )?;
 
                     let mut buf = vec![0; info.buffer_size()];
-                    reader.next_frame(&mut buf).unwrap_or_else(
-                        |err| panic!("Could not read frame: \"{}\"", err)
-                    );
+                    reader.next_frame(&mut buf).map_err(
+                        |err| Error::Font(format!(
+                            "Could not read frame of \"{}\": {}",
+                            path,
+                            err,
+                        ))
+                    )?;
 
                     pixels = buf.clone();
                 },
 
                 Some("char") => {
-                    let bmchar_instance = parse_bmchar(&line_string.clone());
+                    let bmchar_instance = parse_bmchar(&line_string.clone())?;
                     char_map.insert(bmchar_instance.id, bmchar_instance);
                 },
 
@@ -176,17 +204,19 @@ impl Data {
             }
         }
 
-        Data {
-            pixels,
-            common_font_data: CommonFont {
-                line_height,
-                base_height,
-                uv_width,
-                uv_height,
-                char_map,
-                font_face,
-                size,
+        Ok(
+            Data {
+                pixels,
+                common_font_data: CommonFont {
+                    line_height,
+                    base_height,
+                    uv_width,
+                    uv_height,
+                    char_map,
+                    font_face,
+                    size,
+                }
             }
-        }
+        )
     }
 }
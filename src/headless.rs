@@ -0,0 +1,438 @@
+use alg;
+use render;
+use entity;
+use components;
+use input;
+use debug;
+use replay;
+use clock;
+use profile;
+
+use ::{Metadata, ScreenData, Start, Update, FixedUpdate};
+use components::softbody::Iterate;
+
+/// Drives a game without a window or rendering context. \
+/// Time only advances when the caller steps the runner, which makes it
+/// suitable for tests and dedicated servers.
+pub struct Runner<T> {
+    game: T,
+    pub parameters: render::Parameters,
+    pub control:    clock::Control, // Fixed-update time scale and pausing
+    pub input:      input::Manager,
+    pub entities:   entity::Manager,
+    pub components: components::Container,
+    pub debug:      debug::Handler,
+    pub screen:     ScreenData,
+
+    metadata: Metadata,
+    time: f64,
+    clock: clock::Clock,
+    fixed_step_factor: f32, // Frame time per fixed step, relative to fixed_dt
+}
+
+impl<T> Runner<T>
+where
+    T: Start + Update + FixedUpdate + Iterate
+{
+    /// Create the runner and start the game. \
+    /// Model data is only used to resolve model names for the draw
+    /// component; no vertex data is uploaded.
+    pub fn new(model_data: Vec<render::ModelData>, mut game: T) -> Runner<T> {
+        let model_names = model_data.into_iter()
+            .map(|data| data.name)
+            .collect::<Vec<_>>();
+
+        let instances = render::Instances::new(
+            model_names.len(),
+            &model_names,
+            None,
+        );
+
+        let mut entities = entity::Manager::new(1);
+        let mut components = ::init_components(instances);
+
+        // Start game
+        game.start(&mut entities, &mut components);
+
+        Runner {
+            game,
+            parameters: render::Parameters::new(),
+            control: clock::Control::default(),
+            input: input::Manager::new(),
+            entities,
+            components,
+            debug: debug::Handler::new(),
+            screen: ScreenData { width: 1280, height: 720 }, // Default
+            metadata: Metadata::new(),
+            time: 0.0,
+            clock: clock::Clock::new(::FIXED_DT as f64, 0), // Unbounded
+            fixed_step_factor: 1.0,
+        }
+    }
+
+    /// Set the fixed timestep used by both the game and physics. \
+    /// Existing softbody instances keep their velocities.
+    pub fn set_fixed_dt(&mut self, fixed_dt: f32) {
+        self.components.softbodies.set_fixed_dt(fixed_dt);
+        self.clock.set_step((fixed_dt * self.fixed_step_factor) as f64);
+    }
+
+    /// Scale the frame time each fixed step takes, without changing the
+    /// timestep seen by the game and physics (see `fixed_step_factor` in
+    /// the engine config)
+    pub fn set_fixed_step_factor(&mut self, factor: f32) {
+        debug_assert!(factor > 0.0);
+        self.fixed_step_factor = factor;
+
+        let fixed_dt = self.components.softbodies.fixed_dt();
+        self.clock.set_step((fixed_dt * factor) as f64);
+    }
+
+    /// Limit the number of fixed steps run by a single call to `step()`;
+    /// excess time is dropped and recorded in the metadata
+    pub fn set_max_fixed_steps(&mut self, max_steps: u32) {
+        self.clock.set_max_steps(max_steps);
+    }
+
+    /// Advance the clock by `delta` seconds: update the game once, then run
+    /// as many fixed steps as have accumulated
+    pub fn step(&mut self, delta: f64) {
+        debug_assert!(delta >= 0.0);
+        self.time += delta;
+
+        let mut profile = profile::Profile::new();
+
+        // Update game via callback
+        let timer = profile::Timer::start();
+
+        self.game.update(
+            self.time,
+            delta,
+            self.metadata,
+            &mut self.control,
+            self.screen,
+            &mut self.parameters,
+            &mut self.entities,
+            &mut self.components,
+            &mut self.input,
+            &mut self.debug,
+        );
+
+        profile.update = timer.elapsed();
+
+        /* Fixed update loop */
+
+        let (steps, dropped) = self.clock.advance_controlled(
+            delta,
+            &mut self.control,
+        );
+
+        self.metadata.drop_time(dropped);
+
+        for _ in 0..steps {
+            self.step_fixed_raw(&mut profile);
+        }
+
+        self.metadata.alpha = self.clock.alpha();
+        self.finish_frame(delta, profile);
+    }
+
+    /// Run exactly one fixed step (and one frame), bypassing the accumulator
+    pub fn step_fixed(&mut self) {
+        let delta = self.clock.step();
+        self.time += delta;
+
+        let mut profile = profile::Profile::new();
+        self.step_fixed_raw(&mut profile);
+
+        self.metadata.alpha = 0.0;
+        self.finish_frame(delta, profile);
+    }
+
+    /// Run `count` fixed steps
+    pub fn step_fixed_n(&mut self, count: usize) {
+        for _ in 0..count {
+            self.step_fixed();
+        }
+    }
+
+    /// Apply a recorded input snapshot and advance the clock by its
+    /// frame time
+    pub fn step_snapshot(&mut self, snapshot: &input::Snapshot) {
+        self.input.apply(snapshot);
+        self.step(snapshot.delta);
+    }
+
+    /// Play back all remaining frames of a recording, under the fixed
+    /// timestep it was recorded with
+    pub fn replay(&mut self, player: &mut replay::Player) {
+        self.set_fixed_step_factor(player.fixed_step_factor());
+        self.set_fixed_dt(player.fixed_dt());
+
+        while let Some(snapshot) = player.next() {
+            self.step_snapshot(&snapshot);
+        }
+    }
+
+    /// Set the state of a key as if it were pressed or released by the
+    /// player; takes effect on the next step
+    pub fn set_key(&mut self, key: input::Key, pressed: bool) {
+        self.input.set_key_pressed(key as usize, pressed);
+    }
+
+    /// Set mouse movement for the next step
+    pub fn set_mouse_delta(&mut self, delta: alg::Vec2) {
+        self.input.mouse_delta = delta;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        self.metadata
+    }
+
+    pub fn game(&self) -> &T {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut T {
+        &mut self.game
+    }
+
+    /// Consume the runner, returning the game object
+    pub fn into_game(self) -> T {
+        self.game
+    }
+
+    fn step_fixed_raw(&mut self, profile: &mut profile::Profile) {
+        let fixed_dt = self.components.softbodies.fixed_dt();
+
+        ::step_fixed(
+            &mut self.game,
+            self.time,
+            fixed_dt,
+            &mut self.metadata,
+            profile,
+            self.screen,
+            &mut self.parameters,
+            &mut self.entities,
+            &mut self.components,
+            &mut self.input,
+            &mut self.debug,
+        );
+    }
+
+    // No rendering, so only the update, fixed and transfer phases are timed
+    fn finish_frame(&mut self, delta: f64, mut profile: profile::Profile) {
+        ::update_components(
+            delta,
+            self.metadata.alpha,
+            self.screen,
+            &mut profile,
+            &mut self.components,
+            &self.input,
+        );
+
+        // Update last frame of input and reset dirty input
+        self.input.increment_key_states();
+        self.input.mouse_delta = alg::Vec2::zero();
+        self.input.rumbles_lo.clear();
+        self.input.rumbles_hi.clear();
+
+        self.metadata.profile = profile;
+        self.metadata.frame += 1;
+    }
+}
+
+/// Game without callbacks, for tests that only need the engine state
+#[cfg(test)]
+pub struct Empty;
+
+#[cfg(test)]
+default_traits!(Empty, [Start, Update, FixedUpdate, Iterate]);
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use components;
+    use components::Component;
+    use headless::*;
+
+    struct Counter {
+        fixed_updates: usize,
+        body: Option<entity::Handle>,
+    }
+
+    default_traits!(Counter, [Update, Iterate]);
+
+    impl Start for Counter {
+        fn start(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            let body = entities.add();
+            components.transforms.register(body);
+            components.softbodies.register(body);
+            components.softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(alg::Vec3::new(0.0, 2.0, 0.0))
+                .for_entity(body);
+
+            components.softbodies.add_plane(
+                alg::Plane::new(alg::Vec3::up(), 0.0),
+            );
+
+            self.body = Some(body);
+        }
+    }
+
+    impl FixedUpdate for Counter {
+        #[allow(unused_variables)]
+        fn fixed_update(
+            &mut self,
+            time: f64,
+            fixed_delta: f32,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &mut input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            self.fixed_updates += 1;
+        }
+    }
+
+    fn runner() -> Runner<Counter> {
+        Runner::new(vec![], Counter { fixed_updates: 0, body: None })
+    }
+
+    #[test]
+    fn count_fixed_steps() {
+        let mut runner = runner();
+        runner.step_fixed_n(64);
+
+        assert!(runner.game().fixed_updates == 64);
+        assert!(runner.metadata().fixed_frame == 64);
+        assert!(runner.metadata().frame == 64);
+
+        let profile = runner.metadata().profile;
+        assert!(profile.simulate > 0.0);
+        assert!(profile.total() >= profile.simulate);
+    }
+
+    #[test]
+    fn accumulate_fixed_steps() {
+        let mut runner = runner();
+
+        // Frames shorter than the fixed step accumulate
+        for _ in 0..4 {
+            runner.step(::FIXED_DT as f64 * 0.3);
+        }
+
+        assert!(runner.game().fixed_updates == 1);
+        assert!(runner.metadata().frame == 4);
+    }
+
+    #[test]
+    fn limit_fixed_steps() {
+        let mut runner = runner();
+        runner.set_max_fixed_steps(4);
+        runner.set_fixed_dt(::FIXED_DT * 0.5);
+
+        // One second hitch runs only the maximum number of steps
+        runner.step(1.0025);
+
+        assert!(runner.game().fixed_updates == 4);
+        assert!(runner.metadata().dropped_frames == 1);
+        assert!(runner.metadata().dropped_time > 0.9);
+
+        // Frames within the limit drop nothing
+        runner.step(::FIXED_DT as f64);
+
+        assert!(runner.game().fixed_updates == 6);
+        assert!(runner.metadata().dropped_frames == 1);
+    }
+
+    #[test]
+    fn interpolate_children() {
+        use components::query::Query;
+
+        let mut runner = runner();
+        let body = runner.game().body.unwrap();
+
+        // Point light offset from the falling body
+        let child = runner.entities.add();
+        runner.components.transforms.register(child);
+        runner.components.transforms.parent_raw(child, body);
+        runner.components.transforms.set_position(child, alg::Vec3::right());
+        runner.components.lights.register(child);
+        runner.components.lights.build()
+            .point_with_radius(8.0)
+            .for_entity(child);
+
+        runner.step_fixed_n(4);
+
+        // Half a step is left in the accumulator
+        runner.step(::FIXED_DT as f64 * 1.5);
+        let alpha = runner.metadata().alpha;
+        assert!((alpha - 0.5).abs() < 1e-3);
+
+        let transforms = &runner.components.transforms;
+        let parent = transforms.get_mat_interpolated(body, alpha);
+        let position = transforms.get_interpolated(child, alpha).0;
+
+        // Child follows its interpolated parent, not the raw pose
+        assert!(position.dist(parent * alg::Vec3::right()) < 1e-3);
+        assert!(position.dist(transforms.get_position(child)) > 1e-3);
+
+        let light = runner.components.lights.fetch(child).unwrap();
+        assert!(light.vector.dist(position) < 1e-5);
+    }
+
+    #[test]
+    fn follow_interpolated_body() {
+        let mut runner = runner();
+        let body = runner.game().body.unwrap();
+        runner.step_fixed_n(4);
+
+        // Spring starts at rest, so the first update lands on the target
+        let offset = alg::Vec3::new(0.0, 1.0, -4.0);
+        let camera = runner.entities.add();
+        runner.components.transforms.register(camera);
+        runner.components.controllers.register(camera);
+        runner.components.controllers.build_follow(body)
+            .offset(offset)
+            .for_entity(camera);
+
+        // Half a step is left in the accumulator
+        runner.step(::FIXED_DT as f64 * 1.5);
+        let alpha = runner.metadata().alpha;
+        assert!((alpha - 0.5).abs() < 1e-3);
+
+        let transforms = &runner.components.transforms;
+        let target = transforms.get_interpolated(body, alpha).0;
+        let position = transforms.get_position(camera);
+
+        // Camera tracks the rendered pose, not the last or next step
+        assert!(position.dist(target + offset) < 1e-3);
+        assert!(position.dist(transforms.get_position(body) + offset) > 1e-3);
+    }
+
+    #[test]
+    fn settle_on_plane() {
+        let mut runner = runner();
+        runner.step_fixed_n(1024);
+
+        let body = runner.game().body.unwrap();
+        let position = runner.components.transforms.get_position(body);
+
+        // Box of unit size should come to rest on the ground
+        assert!(position.y > 0.25 && position.y < 1.0);
+    }
+}
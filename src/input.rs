@@ -67,6 +67,22 @@ pub enum Key {
     West,
 }
 
+/// Copy of all player input for a single frame, along with the frame time.
+/// Used for recording and replaying sessions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub delta: f64,
+    pub keys: [bool; KEY_COUNT],
+
+    pub cursor_coords: alg::Vec2,
+    pub mouse_delta: alg::Vec2,
+
+    pub joy_l: alg::Vec2,
+    pub joy_r: alg::Vec2,
+    pub trig_l: f32,
+    pub trig_r: f32,
+}
+
 impl Key {
     pub fn from_usize(input: usize) -> Option<Key> {
         match input {
@@ -159,6 +175,38 @@ impl Manager {
         keys
     }
 
+    /* Recording */
+
+    /// Capture the current input state, tagged with the frame time
+    pub fn snapshot(&self, delta: f64) -> Snapshot {
+        Snapshot {
+            delta,
+            keys: self.pressed(),
+            cursor_coords: self.cursor_coords,
+            mouse_delta: self.mouse_delta,
+            joy_l: self.joy_l,
+            joy_r: self.joy_r,
+            trig_l: self.trig_l,
+            trig_r: self.trig_r,
+        }
+    }
+
+    /// Overwrite the current input state with a recorded snapshot \
+    /// Previous key states are preserved, so edge detection (`key_pressed`
+    /// and `key_released`) behaves as it did during recording.
+    pub fn apply(&mut self, snapshot: &Snapshot) {
+        for (i, pressed) in snapshot.keys.iter().enumerate() {
+            self.key_map[i].pressed = *pressed;
+        }
+
+        self.cursor_coords = snapshot.cursor_coords;
+        self.mouse_delta = snapshot.mouse_delta;
+        self.joy_l = snapshot.joy_l;
+        self.joy_r = snapshot.joy_r;
+        self.trig_l = snapshot.trig_l;
+        self.trig_r = snapshot.trig_r;
+    }
+
     /* Gamepad */
 
     pub fn mix_rumble(&self) -> (f32, f32) {
@@ -47,12 +47,21 @@ pub mod input;
 pub mod obj_loader;
 pub mod debug;
 pub mod font;
+pub mod error;
+pub mod headless;
+pub mod replay;
+pub mod profile;
+pub mod scene;
+pub mod prefab;
+pub mod stack;
+pub mod clock;
 mod statics;
 mod util;
 
 use std::thread;
 use components::Component;
 
+// Default fixed timestep, overridden by the engine config
 const FIXED_DT: f32 = 1. / 100.;
 const LIMIT_NS: u32 = 100_000;
 
@@ -61,6 +70,10 @@ pub struct Metadata {
     pub frame: u32,
     pub fixed_frame: u32,
     pub fps: u32,
+    pub dropped_time: f64, // Total frame time skipped by the substep limit
+    pub dropped_frames: u32, // Frames where fixed steps were skipped
+    pub alpha: f32, // Fraction of a fixed step since the last one ran
+    pub profile: profile::Profile, // Phase timings of the previous frame
 }
 
 impl Metadata {
@@ -69,6 +82,18 @@ impl Metadata {
             frame: 0,
             fixed_frame: 0,
             fps: 0,
+            dropped_time: 0.0,
+            dropped_frames: 0,
+            alpha: 0.0,
+            profile: profile::Profile::new(),
+        }
+    }
+
+    // Record time skipped to avoid the spiral of death
+    fn drop_time(&mut self, dropped: f64) {
+        if dropped > 0.0 {
+            self.dropped_time += dropped;
+            self.dropped_frames += 1;
         }
     }
 }
@@ -89,12 +114,14 @@ pub trait Start {
 }
 
 pub trait Update {
+    /// `control` pauses, scales or single-steps the fixed-update loop
     #[allow(unused_variables)]
     fn update(
         &mut self,
         time:  f64,
         delta: f64,
         metadata: Metadata,
+        control: &mut clock::Control,
         screen: ScreenData,
         parameters: &mut render::Parameters,
         entities: &mut entity::Manager,
@@ -120,19 +147,27 @@ pub trait FixedUpdate {
     ) { }
 }
 
-pub fn go<T>(model_data: Vec<render::ModelData>, mut game: T)
+/// Open a window and run the game until it is closed \
+/// Returns an error if the engine fails to start (e.g. a missing config,
+/// font, or Vulkan device) or hits an unrecoverable error while running.
+pub fn go<T>(model_data: Vec<render::ModelData>, mut game: T) -> error::Result<()>
 where
     T: Start + Update + FixedUpdate
         + components::softbody::Iterate
 {
+    // Validate engine configuration before creating any resources
+    let fixed_dt = config::load_positive_setting_or(
+        config::engine_config()?,
+        "settings",
+        "fixed_dt",
+        FIXED_DT,
+    )?;
+
     // Initialize window
-    let (events, window) = init_window();
+    let (events, window) = init_window()?;
 
     // Initialize rendering engine
-    let mut context = match render::Context::new(&window, model_data) {
-        Ok(context) => context,
-        Err(e) => panic!("Could not create Vulkan context: {}", e),
-    };
+    let mut context = render::Context::new(&window, model_data)?;
 
     let mut parameters = render::Parameters::new();
     let instances = render::Instances::new(
@@ -143,10 +178,11 @@ where
 
     /* Initialize input */
 
-    let mut gamepads = match gilrs::GilrsBuilder::new().build() {
-        Ok(pads) => pads,
-        Err(e) => panic!("Could not create gamepad context: {}", e),
-    };
+    let mut gamepads = gilrs::GilrsBuilder::new().build().map_err(
+        |e| error::Error::Gamepad(
+            format!("Could not create gamepad context: {}", e)
+        )
+    )?;
 
     let mut input = input::Manager::new();
 
@@ -154,15 +190,10 @@ where
     let mut entities = entity::Manager::new(1);
 
     // Initialize core components
-    let mut components = components::Container {
-        transforms: components::transform::Manager::new(1),
-        cameras:    components::camera::Manager::new(1),
-        lights:     components::light::Manager::new(8),
-        draws:      components::draw::Manager::new(1, instances),
-        softbodies: components::softbody::Manager::new(1, 1, 1),
-        texts:      components::text::Manager::new(8),
-        labels:     components::label::Manager::new(8),
-    };
+    let mut components = init_components(instances);
+
+    // Fixed timestep must be set before instances are created
+    components.softbodies.set_fixed_dt(fixed_dt);
 
     // Initialize debug struct
     let mut debug = debug::Handler::new();
@@ -171,7 +202,7 @@ where
     game.start(&mut entities, &mut components);
 
     // Initiate update loop
-    begin_update(
+    let result = begin_update(
         game,
         &window,
         events,
@@ -186,20 +217,114 @@ where
 
     // Synchronize before exit
     context.device.wait_idle();
+
+    result
+}
+
+fn init_components(instances: render::Instances) -> components::Container {
+    components::Container {
+        transforms: components::transform::Manager::new(1),
+        cameras:    components::camera::Manager::new(1),
+        lights:     components::light::Manager::new(8),
+        draws:      components::draw::Manager::new(1, instances),
+        softbodies: components::softbody::Manager::new(1, 1, 1),
+        texts:      components::text::Manager::new(8),
+        labels:     components::label::Manager::new(8),
+        controllers: components::controller::Manager::new(1),
+    }
 }
 
-fn init_window() -> (vdw::winit::EventsLoop, vdw::winit::Window) {
+fn init_window() -> error::Result<(
+    vdw::winit::EventsLoop,
+    vdw::winit::Window,
+)> {
+    let config = config::engine_config()?;
+
+    // Missing settings keep the platform defaults
+    let title = config::load_section_setting_or(
+        config,
+        "window",
+        "title",
+        String::new(),
+    )?;
+
+    // Fall back to the package name
+    let title = if title.is_empty() { statics::TITLE.into() } else { title };
+
+    let width = config::load_section_setting_or(
+        config,
+        "window",
+        "width",
+        0u32,
+    )?;
+
+    let height = config::load_section_setting_or(
+        config,
+        "window",
+        "height",
+        0u32,
+    )?;
+
+    let resizable = config::load_section_setting_or(
+        config,
+        "window",
+        "resizable",
+        true,
+    )?;
+
+    let mode = config::load_section_setting_or(
+        config,
+        "window",
+        "mode",
+        "windowed".to_string(),
+    )?;
+
     let events = vdw::winit::EventsLoop::new();
 
-    let window = vdw::winit::WindowBuilder::new()
-        .with_title(statics::TITLE)
-        .build(&events);
+    let mut builder = vdw::winit::WindowBuilder::new()
+        .with_title(title)
+        .with_resizable(resizable);
 
-    if let Err(e) = window {
-        panic!("{}", e);
+    // Zero = use the platform default
+    if width != 0 && height != 0 {
+        builder = builder.with_dimensions(
+            (width, height).into()
+        );
     }
 
-    (events, window.unwrap())
+    builder = match mode.as_str() {
+        "windowed" => builder,
+
+        // Exclusive fullscreen on the primary monitor
+        "fullscreen" => builder.with_fullscreen(
+            Some(events.get_primary_monitor())
+        ),
+
+        // Undecorated window covering the monitor it opens on
+        // (sized and positioned below)
+        "borderless" => builder.with_decorations(false),
+
+        _ => return Err(error::Error::Config(format!(
+            "Invalid window mode \"{}\" \
+            (expected windowed, fullscreen or borderless)",
+            mode,
+        ))),
+    };
+
+    let window = builder.build(&events)
+        .map_err(|e| error::Error::Window(e.to_string()))?;
+
+    // Fit borderless windows to their monitor, whose origin is not the
+    // desktop origin for secondary monitors
+    if mode == "borderless" {
+        let monitor = window.get_current_monitor();
+        let factor = monitor.get_hidpi_factor();
+
+        window.set_position(monitor.get_position().to_logical(factor));
+        window.set_inner_size(monitor.get_dimensions().to_logical(factor));
+    }
+
+    Ok((events, window))
 }
 
 fn begin_update<T>(
@@ -213,26 +338,35 @@ fn begin_update<T>(
     entities:   &mut entity::Manager,
     components: &mut components::Container,
     debug:      &mut debug::Handler,
-) where
+) -> error::Result<()> where
     T: Start + Update + FixedUpdate
         + components::softbody::Iterate
 {
     let mut running = true;
+    let config = config::engine_config()?;
 
     let start = std::time::Instant::now();
-    let mut last_time = 0f64;
-    let mut accumulator = 0f64; // Fixed-framerate accumulator
+    let mut time = 0f64;
+    let mut last_elapsed = 0f64;
     let mut last_updated_counter = start;
     let mut last_updated_renderer = start;
     let mut last_frame = 0u32;
 
     let mut metadata = Metadata::new();
 
+    // Whether focusing the window grabs the mouse cursor
+    let grab_cursor = config::load_section_setting_or(
+        config,
+        "window",
+        "grab_cursor",
+        true,
+    )?;
+
     let show_fps = config::load_section_setting::<bool>(
-        &config::ENGINE_CONFIG,
+        config,
         "settings",
         "show_fps",
-    );
+    )?;
 
     let debug_fps = if show_fps {
         let handle = entities.add();
@@ -250,13 +384,44 @@ fn begin_update<T>(
         Some(handle)
     } else { None };
 
+    let show_profile = config::load_section_setting_or(
+        config,
+        "settings",
+        "show_profile",
+        false,
+    )?;
+
+    // One label per phase, listed below the frame counter
+    let debug_profile = if show_profile {
+        let phases = metadata.profile.phases();
+        let mut handles = Vec::with_capacity(phases.len());
+
+        for (i, (name, _)) in phases.iter().enumerate() {
+            let handle = entities.add();
+            components.transforms.register(handle);
+            components.transforms.set_position(
+                handle,
+                alg::Vec3::new(-1.0, -0.94 + 0.06 * i as f32, 0.),
+            );
+
+            components.labels.register(handle);
+            components.labels.build()
+                .text(&format!("{}: 0.00 ms", name))
+                .for_entity(handle);
+
+            handles.push(handle);
+        }
+
+        Some(handles)
+    } else { None };
+
     /* Frame limiter */
 
     let target_fps = config::load_section_setting::<u32>(
-        &config::ENGINE_CONFIG,
+        config,
         "settings",
         "fps",
-    );
+    )?;
 
     println!("Target frames per second: {}", target_fps);
 
@@ -269,15 +434,88 @@ fn begin_update<T>(
      * useful for debugging physics
      */
 
-    let fixed_step_factor = config::load_section_setting::<f32>(
-        &config::ENGINE_CONFIG,
+    let fixed_step_factor = config::load_positive_setting(
+        config,
         "settings",
         "fixed_step_factor"
+    )?;
+
+    let fixed_dt = components.softbodies.fixed_dt();
+
+    // Maximum number of fixed steps per frame (zero = unbounded);
+    // excess time is dropped so that a stall can't snowball
+    let max_fixed_steps = config::load_section_setting_or(
+        config,
+        "settings",
+        "max_fixed_steps",
+        0,
+    )?;
+
+    let mut clock = clock::Clock::new(
+        (fixed_dt * fixed_step_factor) as f64,
+        max_fixed_steps,
     );
 
-    let fixed_step = (
-        FIXED_DT * fixed_step_factor
-    ) as f64;
+    let mut control = clock::Control::default();
+
+    /* Input recording and replay */
+
+    // Empty or missing = disabled
+    let record_path = config::load_section_setting_or(
+        config,
+        "settings",
+        "record_input",
+        String::new(),
+    )?;
+
+    let mut recorder = if !record_path.is_empty() {
+        match replay::Recorder::new(
+            &record_path,
+            fixed_dt,
+            fixed_step_factor,
+        ) {
+            Ok(recorder) => {
+                println!("Recording input to \"{}\"", record_path);
+                Some(recorder)
+            },
+
+            Err(e) => return Err(e.into()),
+        }
+    } else { None };
+
+    let replay_path = config::load_section_setting_or(
+        config,
+        "settings",
+        "replay_input",
+        String::new(),
+    )?;
+
+    let mut player = if !replay_path.is_empty() {
+        let player = replay::Player::load(&replay_path)?;
+
+        // Playback would diverge under a different fixed step
+        if player.fixed_dt() != fixed_dt
+            || player.fixed_step_factor() != fixed_step_factor
+        {
+            return Err(error::Error::Config(format!(
+                "\"{}\" was recorded with fixed_dt {} and \
+                fixed_step_factor {}, but the config uses {} and {}",
+                replay_path,
+                player.fixed_dt(),
+                player.fixed_step_factor(),
+                fixed_dt,
+                fixed_step_factor,
+            )));
+        }
+
+        println!(
+            "Replaying {} frames of input from \"{}\"",
+            player.len(),
+            replay_path,
+        );
+
+        Some(player)
+    } else { None };
 
     /* Gamepad rumble */
 
@@ -287,6 +525,10 @@ fn begin_update<T>(
                 if pad.is_ff_supported() { Some(id) } else { None }
         ).collect::<Vec<_>>();
 
+    let rumble_error = |e: gilrs::ff::Error| error::Error::Gamepad(
+        format!("Could not create gamepad rumble: {}", e)
+    );
+
     let (rumble_lo, rumble_hi) = {
         let scheduling = gilrs::ff::Replay {
                  after: gilrs::ff::Ticks::from_ms(0),
@@ -311,7 +553,7 @@ fn begin_update<T>(
                     kind: gilrs::ff::BaseEffectType::Weak { magnitude: u16::MAX },
                     scheduling, .. Default::default()
                 }).finish(&mut gamepads)
-                  .expect("Bad gamepad rumble array"),
+                  .map_err(rumble_error)?,
 
             builder_hi
             .add_effect(
@@ -319,16 +561,17 @@ fn begin_update<T>(
                     kind: gilrs::ff::BaseEffectType::Strong { magnitude: u16::MAX },
                     scheduling, .. Default::default()
                 }).finish(&mut gamepads)
-                  .expect("Bad gamepad rumble array"),
+                  .map_err(rumble_error)?,
         )
     };
 
-    rumble_lo.play()
-        .expect("Bad gamepad rumble array");
-    rumble_hi.play()
-        .expect("Bad gamepad rumble array");
+    rumble_lo.play().map_err(rumble_error)?;
+    rumble_hi.play().map_err(rumble_error)?;
 
     loop {
+        let mut profile = profile::Profile::new();
+        let timer = profile::Timer::start();
+
         // Update last frame of input
         input.increment_key_states();
 
@@ -434,16 +677,14 @@ fn begin_update<T>(
         let rumble_mix = input.mix_rumble();
 
         if rumble_mix.0 > 0.0 {
-            match rumble_lo.set_gain(rumble_mix.0) {
-                Ok(_) => (),
-                Err(e) => panic!("Error setting rumble gain: {}", e)
+            if let Err(e) = rumble_lo.set_gain(rumble_mix.0) {
+                eprintln!("Error setting rumble gain: {}", e);
             }
         }
 
         if rumble_mix.1 > 0.0 {
-            match rumble_hi.set_gain(rumble_mix.1) {
-                Ok(_) => (),
-                Err(e) => panic!("Error setting rumble gain: {}", e)
+            if let Err(e) = rumble_hi.set_gain(rumble_mix.1) {
+                eprintln!("Error setting rumble gain: {}", e);
             }
         }
 
@@ -453,6 +694,8 @@ fn begin_update<T>(
         input.rumbles_hi.clear();
 
         // Handle window events
+        let mut window_error = None;
+
         events.poll_events(|event| {
             match event {
                 // Rebuild the swapchain if the window changes size
@@ -464,7 +707,7 @@ fn begin_update<T>(
                     if width == 0 || height == 0 { return; }
 
                     if let Err(e) = context.refresh_swapchain(width, height) {
-                        panic!("{}", e);
+                        window_error = Some(e);
                     }
                 },
 
@@ -480,7 +723,7 @@ fn begin_update<T>(
                 vdw::winit::Event::WindowEvent {
                     event: vdw::winit::WindowEvent::Focused(focused),
                     ..
-                } => {
+                } if grab_cursor => {
                     if let Err(e) = window.grab_cursor(focused) {
                         eprintln!("{}", e);
                     }
@@ -498,6 +741,16 @@ fn begin_update<T>(
                     },
                     ..
                 }=> {
+                    // Time controls are not part of the input snapshot,
+                    // so they would break recording and replay
+                    #[cfg(debug_assertions)] {
+                        if state == vdw::winit::ElementState::Pressed
+                            && recorder.is_none() && player.is_none()
+                        {
+                            debug_time_key(virtual_keycode, &mut control);
+                        }
+                    }
+
                     if let Some(keycode) = vdw_key_to_key(virtual_keycode) {
                         input.set_key_pressed(
                             keycode as usize,
@@ -539,18 +792,56 @@ fn begin_update<T>(
             }
         });
 
-        if !running { break; }
+        // Irrecoverable error
+        if let Some(e) = window_error {
+            return Err(e.into());
+        }
+
+        profile.events = timer.elapsed();
+
+        if !running {
+            if let Some(ref mut recorder) = recorder {
+                if let Err(e) = recorder.flush() {
+                    eprintln!("Failed to save input recording: {}", e);
+                }
+            }
+
+            break;
+        }
 
         /* Time calculations */
 
         let now = std::time::Instant::now();
         let duration = now.duration_since(start);
 
-        let time = duration.as_secs() as f64
+        let elapsed = duration.as_secs() as f64
             + (duration.subsec_nanos() as f64 / 1_000_000_000.);
 
-        let delta = time - last_time;
-        last_time = time;
+        let mut delta = elapsed - last_elapsed;
+        last_elapsed = elapsed;
+
+        // Override live input and frame time with the recording
+        let finished = if let Some(ref mut player) = player {
+            if let Some(snapshot) = player.next() {
+                input.apply(&snapshot);
+                delta = snapshot.delta;
+            }
+
+            player.finished()
+        } else { false };
+
+        if finished {
+            println!("Input replay finished");
+            player = None;
+        }
+
+        if let Some(ref mut recorder) = recorder {
+            if let Err(e) = recorder.record(&input.snapshot(delta)) {
+                eprintln!("Failed to record input: {}", e);
+            }
+        }
+
+        time += delta;
 
         // Screen data
         let screen = {
@@ -563,10 +854,13 @@ fn begin_update<T>(
         };
 
         // Update game via callback
+        let timer = profile::Timer::start();
+
         game.update(
             time,
             delta,
             metadata,
+            &mut control,
             screen,
             parameters,
             entities,
@@ -575,15 +869,21 @@ fn begin_update<T>(
             debug,
         );
 
+        profile.update = timer.elapsed();
+
         /* Fixed update loop */
 
-        accumulator += delta;
+        let (steps, dropped) = clock.advance_controlled(delta, &mut control);
+
+        metadata.drop_time(dropped);
 
-        while accumulator >= fixed_step {
-            game.fixed_update(
+        for _ in 0..steps {
+            step_fixed(
+                &mut game,
                 time,
-                FIXED_DT,
-                metadata,
+                fixed_dt,
+                &mut metadata,
+                &mut profile,
                 screen,
                 parameters,
                 entities,
@@ -591,47 +891,42 @@ fn begin_update<T>(
                 input,
                 debug,
             );
-
-            // Update physics component
-            components.softbodies.simulate(
-                &mut game,
-                &mut components.transforms,
-            );
-
-            accumulator -= fixed_step;
-            metadata.fixed_frame += 1;
         }
 
-        // Update render-related components
-        components.lights.update(&components.transforms);
-        components.draws.transfer(
-            &components.transforms,
-            &components.softbodies,
-            &components.lights,
+        metadata.alpha = clock.alpha();
+
+        update_components(
+            delta,
+            metadata.alpha,
+            screen,
+            &mut profile,
+            components,
+            input,
         );
 
-        // Get shared UBO from camera component
-        let shared_ubo = components.cameras.compute(
+        // Get shared UBOs and viewports from camera component
+        let views = components.cameras.compute_views(
             &components.transforms,
             screen,
         );
 
-        components.texts.update(&components.transforms);
-        components.labels.update(&components.transforms, screen);
-
         // Update renderer
+        let timer = profile::Timer::start();
+
         if let Err(e) = context.update(
             &components.draws.instances,
-            shared_ubo,
+            &views,
         ) {
             // Irrecoverable error
-            panic!("{}", e);
+            return Err(e.into());
         }
 
+        profile.render_update = timer.elapsed();
+
         #[cfg(debug_assertions)] {
             if let Err(e) = context.update_debug(&debug.lines) {
                 // Irrecoverable error
-                panic!("{}", e);
+                return Err(e.into());
             }
         }
 
@@ -659,6 +954,8 @@ fn begin_update<T>(
         last_updated_renderer = now;
 
         // Render frame
+        let timer = profile::Timer::start();
+
         if let Err(e) = context.draw(
             &parameters,
             &components.draws.instances,
@@ -689,9 +986,12 @@ fn begin_update<T>(
             }
 
             // Irrecoverable error
-            panic!("{}", e);
+            return Err(e.into());
         }
 
+        profile.render_draw = timer.elapsed();
+        metadata.profile = profile;
+
         // Increment frame counter
         metadata.frame += 1;
 
@@ -706,9 +1006,135 @@ fn begin_update<T>(
                     .for_entity(debug_fps.unwrap());
             }
 
+            if let Some(ref handles) = debug_profile {
+                let phases = metadata.profile.phases();
+
+                for (handle, (name, seconds)) in handles.iter().zip(&phases) {
+                    components.labels.set_str(
+                        *handle,
+                        &format!("{}: {:.2} ms", name, seconds * 1000.),
+                    );
+                }
+            }
+
             last_updated_counter = now;
         }
     }
+
+    Ok(())
+}
+
+/// Run a single fixed step: the game callback followed by physics
+fn step_fixed<T>(
+    game:       &mut T,
+    time:       f64,
+    fixed_dt:   f32,
+    metadata:   &mut Metadata,
+    profile:    &mut profile::Profile,
+    screen:     ScreenData,
+    parameters: &mut render::Parameters,
+    entities:   &mut entity::Manager,
+    components: &mut components::Container,
+    input:      &mut input::Manager,
+    debug:      &mut debug::Handler,
+) where
+    T: FixedUpdate + components::softbody::Iterate
+{
+    components.transforms.begin_step();
+    let timer = profile::Timer::start();
+
+    game.fixed_update(
+        time,
+        fixed_dt,
+        *metadata,
+        screen,
+        parameters,
+        entities,
+        components,
+        input,
+        debug,
+    );
+
+    profile.fixed_update += timer.elapsed();
+
+    // Update physics component
+    let timer = profile::Timer::start();
+
+    components.softbodies.simulate(
+        game,
+        &mut components.transforms,
+    );
+
+    profile.simulate += timer.elapsed();
+    metadata.fixed_frame += 1;
+}
+
+/// Update components once per frame, after the fixed steps: controllers
+/// first, then everything that is rendered. \
+/// Shared by the windowed and headless loops so that their ordering
+/// cannot drift apart.
+fn update_components(
+    delta:      f64,
+    alpha:      f32,
+    screen:     ScreenData,
+    profile:    &mut profile::Profile,
+    components: &mut components::Container,
+    input:      &input::Manager,
+) {
+    // Controllers track targets at their propagated world pose
+    components.transforms.update();
+    components.controllers.update(
+        delta as f32,
+        alpha,
+        input,
+        &mut components.transforms,
+    );
+
+    // Update render-related components
+    let timer = profile::Timer::start();
+
+    // Propagate again, since controllers move their transforms (and any
+    // children) after the first pass; only those are dirty by now
+    components.transforms.update();
+    components.lights.update(&components.transforms, alpha);
+    components.draws.transfer(
+        &components.transforms,
+        &components.softbodies,
+        &components.lights,
+        alpha,
+    );
+
+    profile.transfer = timer.elapsed();
+
+    components.texts.update(&components.transforms);
+    components.labels.update(&components.transforms, screen);
+}
+
+/// Debug hotkeys for physics: F5 toggles pause, F6 runs a single fixed
+/// step, and F7/F8 halve/double the time scale. \
+/// Ignored while recording or replaying input.
+#[cfg(debug_assertions)]
+fn debug_time_key(
+    keycode: vdw::winit::VirtualKeyCode,
+    control: &mut clock::Control,
+) {
+    use vdw::winit::VirtualKeyCode;
+
+    match keycode {
+        VirtualKeyCode::F5 => control.toggle_pause(),
+        VirtualKeyCode::F6 => control.step(1),
+        VirtualKeyCode::F7 => {
+            let scale = control.scale() * 0.5;
+            control.set_scale(scale);
+            println!("Time scale: {}", scale);
+        },
+        VirtualKeyCode::F8 => {
+            let scale = control.scale() * 2.0;
+            control.set_scale(scale);
+            println!("Time scale: {}", scale);
+        },
+        _ => (),
+    }
 }
 
 fn vdw_key_to_key(keycode: vdw::winit::VirtualKeyCode) -> Option<input::Key> {
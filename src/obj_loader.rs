@@ -5,6 +5,8 @@ use alg;
 use graphics;
 use render;
 
+use error::{Error, Result};
+
 /// Load obj meshes from path to vector of `render::ModelData` \
 /// If the file contains normal data, it will be used.
 /// Otherwise, normals are computed using `render::NormalMode::Smooth`. \
@@ -12,13 +14,13 @@ use render;
 pub fn load_obj(
     filename: &str,
     color: graphics::Color,
-) -> Vec<render::ModelData> {
+) -> Result<Vec<render::ModelData>> {
     let tobj_models = tobj::load_obj(&std::path::Path::new(filename));
-    let (models, _) = tobj_models.unwrap_or_else(
-        |err| panic!(
-            "Could not load obj file: \"{}\"", err
-        )
-    );
+    let (models, _) = tobj_models.map_err(
+        |err| Error::Model(format!(
+            "Could not load obj file \"{}\": {}", filename, err
+        ))
+    )?;
 
     let mut result = Vec::new();
 
@@ -136,5 +138,5 @@ pub fn load_obj(
         result.len(),
     );
 
-    result
+    Ok(result)
 }
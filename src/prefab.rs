@@ -0,0 +1,497 @@
+use std;
+
+use alg;
+use render;
+use entity;
+use components;
+
+use components::Component;
+use components::softbody::JointDescription;
+
+/// Reusable template for an entity hierarchy. \
+/// Parts are placed relative to the prefab origin and spawned together,
+/// along with their transform parents and softbody joints.
+#[derive(Default)]
+pub struct Prefab {
+    parts: Vec<Part>,
+    joints: Vec<Joint>,
+}
+
+struct Part {
+    position: alg::Vec3,
+    orientation: alg::Quat,
+    scale: alg::Vec3,
+    parent: Option<usize>, // Transform parent
+    tags: Vec<String>,
+    model: Option<String>,
+    light: Option<render::Light>,
+    limb: Option<Limb>,
+}
+
+impl Part {
+    fn new() -> Part {
+        Part {
+            position: alg::Vec3::zero(),
+            orientation: alg::Quat::id(),
+            scale: alg::Vec3::one(),
+            parent: None,
+            tags: Vec::new(),
+            model: None,
+            light: None,
+            limb: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Limb {
+    scale: alg::Vec3,
+    mass: f32,
+    rigidity: f32,
+}
+
+// Same as `JointDescription`, with part indices in place of handles
+#[derive(Clone, Copy)]
+struct Joint {
+    parent: usize,
+    child: usize,
+    x: (f32, f32),
+    y: (f32, f32),
+    z: (f32, f32),
+    unlocked: bool,
+    fwd: alg::Vec3,
+    up: alg::Vec3,
+    offset: alg::Vec3,
+}
+
+impl Prefab {
+    pub fn new() -> Prefab {
+        Prefab::default()
+    }
+
+    /// Get part builder that can be used to add a part to the prefab
+    pub fn part(&mut self) -> PartBuilder {
+        PartBuilder::new(self)
+    }
+
+    /// Get joint builder for two softbody limb parts
+    pub fn joint(&mut self, parent: usize, child: usize) -> JointBuilder {
+        JointBuilder::new(self, parent, child)
+    }
+
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Create the prefab's entities at the given transform. \
+    /// Returns entity handles in the order the parts were added.
+    pub fn spawn(
+        &self,
+        position: alg::Vec3,
+        orientation: alg::Quat,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) -> Vec<entity::Handle> {
+        let mut handles = Vec::with_capacity(self.parts.len());
+
+        for (i, part) in self.parts.iter().enumerate() {
+            let entity = entities.add();
+
+            for tag in &part.tags {
+                entities.add_tag(entity, tag);
+            }
+
+            let part_position = position + orientation * part.position;
+            let part_orientation = orientation * part.orientation;
+
+            components.transforms.register(entity);
+            components.transforms.set(
+                entity,
+                part_position,
+                part_orientation,
+                part.scale,
+            );
+
+            if let Some(ref model) = part.model {
+                components.draws.register(entity);
+                components.draws.bind_model(entity, model);
+            }
+
+            if let Some(light) = part.light {
+                components.lights.register(entity);
+                components.lights.set(entity, light);
+            }
+
+            if let Some(limb) = part.limb {
+                components.softbodies.register(entity);
+                components.softbodies.build_instance()
+                    .make_box_limb(limb.scale)
+                    .mass(limb.mass)
+                    .rigidity(limb.rigidity)
+                    .initial_pos(part_position)
+                    .for_entity(entity);
+
+                // Box limbs are created axis-aligned; joint children are
+                // aligned with their parent when the joint is added
+                if !self.joints.iter().any(|joint| joint.child == i) {
+                    let instance = components.softbodies
+                        .get_mut_instance(entity);
+
+                    instance.rotate_around(part_orientation, part_position);
+                    instance.lock();
+                }
+            }
+
+            handles.push(entity);
+        }
+
+        for (part, entity) in self.parts.iter().zip(&handles) {
+            if let Some(parent) = part.parent {
+                components.transforms.parent(*entity, handles[parent]);
+            }
+        }
+
+        // Joints orient their children relative to the parent
+        for joint in &self.joints {
+            components.softbodies.add_joint_description(JointDescription {
+                parent: handles[joint.parent],
+                child: handles[joint.child],
+                x: joint.x,
+                y: joint.y,
+                z: joint.z,
+                unlocked: joint.unlocked,
+                fwd: joint.fwd,
+                up: joint.up,
+                offset: joint.offset,
+            });
+        }
+
+        handles
+    }
+}
+
+/// Builder pattern for prefab parts
+pub struct PartBuilder<'a> {
+    prefab: &'a mut Prefab,
+    part: Part,
+}
+
+impl<'a> PartBuilder<'a> {
+    pub fn new(prefab: &'a mut Prefab) -> PartBuilder<'a> {
+        PartBuilder {
+            prefab,
+            part: Part::new(),
+        }
+    }
+
+    /// Position relative to the prefab origin
+    pub fn position(&mut self, position: alg::Vec3) -> &mut PartBuilder<'a> {
+        self.part.position = position;
+        self
+    }
+
+    /// Orientation relative to the prefab origin
+    pub fn orientation(
+        &mut self,
+        orientation: alg::Quat,
+    ) -> &mut PartBuilder<'a> {
+        self.part.orientation = orientation;
+        self
+    }
+
+    pub fn scale(&mut self, scale: alg::Vec3) -> &mut PartBuilder<'a> {
+        self.part.scale = scale;
+        self
+    }
+
+    /// Transform parent, given the index of a previously added part
+    pub fn parent(&mut self, parent: usize) -> &mut PartBuilder<'a> {
+        assert!(
+            parent < self.prefab.parts.len(),
+            "Prefab part parent {} has not been added",
+            parent,
+        );
+
+        self.part.parent = Some(parent);
+        self
+    }
+
+    pub fn tag(&mut self, tag: &str) -> &mut PartBuilder<'a> {
+        self.part.tags.push(tag.to_string());
+        self
+    }
+
+    /// Bind draw component to model with given name
+    pub fn model(&mut self, name: &str) -> &mut PartBuilder<'a> {
+        self.part.model = Some(name.to_string());
+        self
+    }
+
+    pub fn light(&mut self, light: render::Light) -> &mut PartBuilder<'a> {
+        self.part.light = Some(light);
+        self
+    }
+
+    /// Create a softbody box limb with the given scale
+    pub fn box_limb(&mut self, scale: alg::Vec3) -> &mut PartBuilder<'a> {
+        self.part.limb = Some(
+            Limb {
+                scale,
+                mass: components::softbody::INST_DEFAULT_MASS,
+                rigidity: components::softbody::INST_DEFAULT_RIGID,
+            }
+        );
+
+        self
+    }
+
+    /// Limb mass; requires `box_limb(...)`
+    pub fn mass(&mut self, mass: f32) -> &mut PartBuilder<'a> {
+        self.part.limb.as_mut()
+            .expect("Part mass set without a limb")
+            .mass = mass;
+
+        self
+    }
+
+    /// Limb rigidity, in the range (0, 1]; requires `box_limb(...)`
+    pub fn rigidity(&mut self, rigidity: f32) -> &mut PartBuilder<'a> {
+        debug_assert!(rigidity > 0.0 && rigidity <= 1.0);
+
+        self.part.limb.as_mut()
+            .expect("Part rigidity set without a limb")
+            .rigidity = rigidity;
+
+        self
+    }
+
+    /// Finalize, returning the index of the new part
+    pub fn add(&mut self) -> usize {
+        let part = std::mem::replace(&mut self.part, Part::new());
+        self.prefab.parts.push(part);
+        self.prefab.parts.len() - 1
+    }
+}
+
+/// Builder pattern for joints between prefab limbs \
+/// Limits and transform behave as in `softbody::JointBuilder`
+pub struct JointBuilder<'a> {
+    prefab: &'a mut Prefab,
+    joint: Joint,
+}
+
+impl<'a> JointBuilder<'a> {
+    pub fn new(
+        prefab: &'a mut Prefab,
+        parent: usize,
+        child: usize,
+    ) -> JointBuilder<'a> {
+        debug_assert!(parent != child);
+
+        JointBuilder {
+            prefab,
+            joint: Joint {
+                parent,
+                child,
+                x: (0.0, 0.0),
+                y: (0.0, 0.0),
+                z: (0.0, 0.0),
+                unlocked: false,
+                fwd: alg::Vec3::fwd(),
+                up: alg::Vec3::up(),
+                offset: alg::Vec3::zero(),
+            },
+        }
+    }
+
+    /// Joint x-axis limit range, in degrees
+    pub fn x(&mut self, min: f32, max: f32) -> &mut JointBuilder<'a> {
+        self.joint.x = (min, max);
+        self
+    }
+
+    /// Joint y-axis limit range, in degrees
+    pub fn y(&mut self, min: f32, max: f32) -> &mut JointBuilder<'a> {
+        self.joint.y = (min, max);
+        self
+    }
+
+    /// Joint z-axis limit range, in degrees
+    pub fn z(&mut self, min: f32, max: f32) -> &mut JointBuilder<'a> {
+        self.joint.z = (min, max);
+        self
+    }
+
+    pub fn xyz(&mut self, min: f32, max: f32) -> &mut JointBuilder<'a> {
+        self.x(min, max).y(min, max).z(min, max)
+    }
+
+    /// Unlocked joints will ignore all rotational limits
+    pub fn unlock(&mut self) -> &mut JointBuilder<'a> {
+        self.joint.unlocked = true;
+        self
+    }
+
+    pub fn fwd(&mut self, fwd: alg::Vec3) -> &mut JointBuilder<'a> {
+        self.joint.fwd = fwd;
+        self
+    }
+
+    pub fn up(&mut self, up: alg::Vec3) -> &mut JointBuilder<'a> {
+        self.joint.up = up;
+        self
+    }
+
+    pub fn offset(&mut self, offset: alg::Vec3) -> &mut JointBuilder<'a> {
+        self.joint.offset = offset;
+        self
+    }
+
+    /// Finalize \
+    /// Panics if either part is missing or is not a limb, rather than
+    /// failing later in `Prefab::spawn()`.
+    pub fn add(&mut self) {
+        let is_limb = |i: usize| self.prefab.parts.get(i)
+            .map_or(false, |part| part.limb.is_some());
+
+        if !is_limb(self.joint.parent) || !is_limb(self.joint.child) {
+            panic!(
+                "Prefab joint between parts {} and {} must connect two limbs",
+                self.joint.parent,
+                self.joint.child,
+            );
+        }
+
+        self.prefab.joints.push(self.joint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use headless;
+    use prefab::*;
+
+    #[test]
+    fn spawn_twice() {
+        let mut mech = Prefab::new();
+        assert!(mech.is_empty());
+
+        let body = mech.part()
+            .box_limb(alg::Vec3::one())
+            .mass(10.0)
+            .tag("mech")
+            .add();
+
+        let arm = mech.part()
+            .position(alg::Vec3::fwd())
+            .box_limb(alg::Vec3::one())
+            .mass(10.0)
+            .add();
+
+        let lamp = mech.part()
+            .position(alg::Vec3::up())
+            .parent(body)
+            .add();
+
+        mech.joint(body, arm)
+            .offset(alg::Vec3::fwd() * 0.5)
+            .xyz(-45.0, 45.0)
+            .add();
+
+        let mut runner = headless::Runner::new(vec![], headless::Empty);
+        let mut spawned = Vec::new();
+
+        for i in 0..2 {
+            let position = alg::Vec3::right() * (i as f32 * 4.0);
+            let orientation = alg::Quat::axis_angle(
+                alg::Vec3::up(),
+                i as f32,
+            );
+
+            let handles = mech.spawn(
+                position,
+                orientation,
+                &mut runner.entities,
+                &mut runner.components,
+            );
+
+            assert!(handles.len() == mech.len());
+
+            // Parts are placed relative to the spawn transform
+            let transforms = &runner.components.transforms;
+            let expected = [
+                position,
+                position + orientation * alg::Vec3::fwd(),
+                position + orientation * alg::Vec3::up(),
+            ];
+
+            for (part, expected) in [body, arm, lamp].iter().zip(&expected) {
+                let handle = handles[*part];
+                let (actual, actual_orientation, _) = transforms.get(handle);
+
+                assert!(actual.dist(*expected) < 1e-4);
+                assert!(actual_orientation.dot(orientation).abs() > 1.0 - 1e-4);
+            }
+
+            let parent = transforms.get_parent(handles[lamp]);
+            assert!(parent == Some(handles[body]));
+
+            // Limbs are rotated along with their transforms; the arm
+            // follows the body through the joint
+            for part in &[body, arm] {
+                let instance = runner.components.softbodies
+                    .get_instance(handles[*part]);
+
+                let fwd = instance.matched_orientation(instance.center())
+                    * alg::Vec3::fwd();
+
+                assert!(fwd.dist(orientation * alg::Vec3::fwd()) < 1e-4);
+            }
+
+            let body_instance = runner.components.softbodies
+                .get_instance(handles[body]);
+
+            assert!(body_instance.center().dist(position) < 1e-4);
+
+            spawned.push(handles);
+        }
+
+        assert!(runner.entities.tagged("mech").count() == 2);
+        assert!(runner.components.softbodies.count() == 4);
+        assert!(runner.components.softbodies.joints().len() == 2);
+
+        runner.step_fixed_n(16);
+
+        // Each arm stays attached to its own body, and lamps follow
+        for handles in &spawned {
+            let softbodies = &runner.components.softbodies;
+            let body_center = softbodies.get_instance(handles[body]).center();
+            let arm_center = softbodies.get_instance(handles[arm]).center();
+            assert!(body_center.dist(arm_center) < 2.0);
+
+            let transforms = &runner.components.transforms;
+            let lamp_position = transforms.get_position(handles[lamp]);
+            let expected = transforms.transform_point(
+                handles[body],
+                alg::Vec3::up(),
+            );
+
+            assert!(lamp_position.dist(expected) < 1e-4);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_invalid_joint() {
+        let mut prefab = Prefab::new();
+        let body = prefab.part().box_limb(alg::Vec3::one()).add();
+
+        // Checked in release builds too, not only when spawning
+        prefab.joint(body, body + 1).add();
+    }
+}
@@ -0,0 +1,62 @@
+use std;
+
+/// Time spent in each phase of a frame, in seconds
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Profile {
+    pub events: f64, // Window and gamepad event polling
+    pub update: f64, // `Update` callback
+    pub fixed_update: f64, // `FixedUpdate` callbacks, summed over fixed steps
+    pub simulate: f64, // Softbody simulation, summed over fixed steps
+    pub transfer: f64, // Light and draw component transfer
+    pub render_update: f64, // `Context::update`
+    pub render_draw: f64, // `Context::draw`
+}
+
+impl Profile {
+    pub fn new() -> Profile {
+        Profile::default()
+    }
+
+    /// Sum of all phases (excludes time spent in the frame limiter)
+    pub fn total(&self) -> f64 {
+        self.events
+            + self.update
+            + self.fixed_update
+            + self.simulate
+            + self.transfer
+            + self.render_update
+            + self.render_draw
+    }
+
+    /// Returns (name, seconds) for each phase, in frame order
+    pub fn phases(&self) -> [(&'static str, f64); 7] {
+        [
+            ("Events", self.events),
+            ("Update", self.update),
+            ("Fixed update", self.fixed_update),
+            ("Simulate", self.simulate),
+            ("Transfer", self.transfer),
+            ("Render update", self.render_update),
+            ("Render draw", self.render_draw),
+        ]
+    }
+}
+
+/// Stopwatch for a single phase
+pub(crate) struct Timer {
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub fn start() -> Timer {
+        Timer { start: std::time::Instant::now() }
+    }
+
+    /// Seconds since the timer was started
+    pub fn elapsed(&self) -> f64 {
+        let duration = self.start.elapsed();
+
+        duration.as_secs() as f64
+            + (duration.subsec_nanos() as f64 / 1_000_000_000.)
+    }
+}
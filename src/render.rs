@@ -9,6 +9,7 @@ use config;
 use statics;
 use util;
 use font;
+use error;
 
 macro_rules! offset_of {
     ($struct:ty, $field:tt) => (
@@ -41,13 +42,14 @@ const DYNAMIC_UBO_WIDTH: usize = 2020;
 
 pub const MAX_SOFTBODY_VERT: usize = (
     DYNAMIC_UBO_WIDTH
-        - std::mem::size_of::<alg::Mat4>()
+        - std::mem::size_of::<alg::Mat4>() * 2 // Model and normal matrices
         - std::mem::size_of::<[Light; MAX_INSTANCE_LIGHTS]>()
         - 4 // Base vertex (no padding)
 ) / std::mem::size_of::<PaddedVec3>()
   / 2; // There are two offset arrays
 
 pub const MAX_INSTANCE_LIGHTS: usize = 4;
+pub const MAX_VIEWPORTS: usize = 4;
 
 const MAX_CHAR_COUNT: u32 = 2048;
 const MAX_INSTANCE_TEXTS: usize = 64;
@@ -66,6 +68,7 @@ pub struct Context<'a> {
     sharing_mode:   vd::SharingMode,
     q_indices:      Vec<u32>,
     present_mode:   vd::PresentModeKhr,
+    shader_path:    String,
 
     /* Fixed information */
 
@@ -87,6 +90,7 @@ pub struct Context<'a> {
     pipeline:        vd::GraphicsPipeline,
     framebuffers:    Vec<vd::Framebuffer>,
     ubo_alignment:   u64,
+    shared_alignment: u64,
     descriptor_sets: Vec<vd::DescriptorSet>,
     command_buffers: Vec<vd::CommandBuffer>,
 
@@ -115,6 +119,11 @@ pub struct Context<'a> {
     debug_data: Option<DebugData>,
     debug_line_count: u32,
 
+    /* Per-frame view data */
+
+    viewports: Vec<Viewport>, // One shared UBO each, in draw order
+    visible: Vec<bool>, // Per viewport and instance slot, in draw order
+
     /* Persistent data */
 
     _vert_mod:        vd::ShaderModule,
@@ -128,7 +137,23 @@ impl<'a> Context<'a> {
     pub fn new(
         window:     &vdw::winit::Window,
         model_data: Vec<ModelData>,
-    ) -> vd::Result<Context> {
+    ) -> error::Result<Context> {
+        let config = config::engine_config()?;
+
+        let shader_path = config::load_section_setting::<String>(
+            config,
+            "settings",
+            "shader_path",
+        )?;
+
+        let font_path = config::load_section_setting::<String>(
+            config,
+            "settings",
+            "font_path",
+        )?;
+
+        let font_data = font::Data::new(&font_path)?;
+
         let (
             surface,
             graphics_family,
@@ -148,7 +173,7 @@ impl<'a> Context<'a> {
             _vert_mod,
             _frag_mod,
             shader_stages,
-        ) = load_shaders(device.clone())?;
+        ) = load_shaders(device.clone(), &shader_path)?;
 
         let (
             vertex_buffer,
@@ -208,6 +233,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &pipeline_layout,
             &device,
+            &shader_path,
         )?;
 
         let debug_line_count = 0;
@@ -240,13 +266,6 @@ impl<'a> Context<'a> {
 
         /* Text data */
 
-        let font_path = config::load_section_setting::<String>(
-            &config::ENGINE_CONFIG,
-            "settings",
-            "font_path",
-        );
-        let font_data = font::Data::new(&font_path);
-
         let text_meta = init_text_pipeline_builder(
             &swapchain.extent(),
             device.clone(),
@@ -265,6 +284,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &text_meta,
             false,
+            &shader_path,
         )?;
 
         let label_display = create_text(
@@ -277,6 +297,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &text_meta,
             true,
+            &shader_path,
         )?;
 
         // Return newly-built context structure
@@ -291,6 +312,7 @@ impl<'a> Context<'a> {
                 sharing_mode,
                 q_indices,
                 present_mode,
+                shader_path,
                 graphics_family,
                 present_family,
                 drawing_pool,
@@ -309,6 +331,7 @@ impl<'a> Context<'a> {
                 pipeline,
                 framebuffers,
                 ubo_alignment,
+                shared_alignment,
                 descriptor_sets,
                 command_buffers,
                 vertex_buffer,
@@ -327,6 +350,8 @@ impl<'a> Context<'a> {
                 font_alignment,
                 debug_data,
                 debug_line_count,
+                viewports: Vec::new(),
+                visible: Vec::new(),
                 _vert_mod,
                 _frag_mod,
                 _depth_image,
@@ -373,6 +398,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &self.pipeline_layout,
             &self.device,
+            &self.shader_path,
         )?;
 
         let (
@@ -413,6 +439,7 @@ impl<'a> Context<'a> {
         self.pipeline = pipeline;
         self.framebuffers = framebuffers;
         self.ubo_alignment = ubo_alignment;
+        self.shared_alignment = shared_alignment;
         self.font_alignment = font_alignment;
         self.descriptor_sets = descriptor_sets;
         self.command_buffers = command_buffers;
@@ -441,6 +468,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &self.text_meta,
             false,
+            &self.shader_path,
         )?;
 
         self.label_display = create_text(
@@ -453,6 +481,7 @@ impl<'a> Context<'a> {
             &render_pass,
             &self.text_meta,
             true,
+            &self.shader_path,
         )?;
 
         self.swapchain = swapchain;
@@ -499,23 +528,41 @@ impl<'a> Context<'a> {
         Ok(())
     }
 
-    /// Update rendering data and transfer to GPU
+    /// Update rendering data and transfer to GPU. \
+    /// Each view is drawn into its viewport, in order.
     pub fn update(
         &mut self,
         instances: &Instances,
-        shared_ubo: SharedUBO,
+        views: &[(SharedUBO, Viewport)],
     ) -> vd::Result<()> {
-        /* Copy shared UBO to GPU */
+        if views.is_empty() || views.len() > MAX_VIEWPORTS {
+            return Err("Invalid number of viewports".into());
+        }
+
+        /* Copy shared UBOs to GPU */
+
+        let mut shared_buffer = util::AlignedBuffer::<SharedUBO>::new(
+            self.shared_alignment as usize,
+            views.len(),
+        );
+
+        for (shared_ubo, _) in views {
+            shared_buffer.push(*shared_ubo);
+        }
 
         unsafe {
             copy_buffer(
                 &self.device,
                 self.ubo_memory,
-                std::mem::size_of::<SharedUBO>() as u64,
-                &[shared_ubo],
+                shared_buffer.size() as u64,
+                &shared_buffer.finalize(),
             )?;
         }
 
+        self.viewports.clear();
+        self.viewports.extend(views.iter().map(|view| view.1));
+        self.visible.clear();
+
         /* Copy instance UBOs to GPU */
 
         let count = instances.count();
@@ -538,6 +585,26 @@ impl<'a> Context<'a> {
             }
         }
 
+        /* Cull against each camera frustum */
+
+        for (shared_ubo, _) in views {
+            let frustum = alg::Frustum::from_mat(
+                shared_ubo.projection * shared_ubo.view,
+            );
+
+            for (i, model) in instances.data.iter().enumerate() {
+                for entry in model {
+                    self.visible.push(
+                        !entry.1.hide && self.models[i].visible(
+                            &frustum,
+                            entry.0.model,
+                            entry.1.extent,
+                        )
+                    );
+                }
+            }
+        }
+
         unsafe {
             copy_buffer(
                 &self.device,
@@ -664,33 +731,68 @@ impl<'a> Context<'a> {
 
         debug_assert!(self.models.len() == instances.data.len());
 
-        let mut instance = 0;
-        for j in 0..self.models.len() {
-            // Render each instance
-            for k in 0..instances.data[j].len() {
-                // Bind uniform data
-                cmd_buffer.bind_descriptor_sets(
-                    vd::PipelineBindPoint::Graphics,
-                    &self.pipeline_layout,
-                    0,
-                    &[&self.descriptor_sets[0]], // Single descriptor set
-                    // Offset dynamic uniform buffer
-                    &[self.ubo_alignment as u32 * instance as u32],
-                );
+        let extent = self.swapchain.extent().clone();
+        let mut visible = self.visible.iter();
+
+        for (v, viewport) in self.viewports.iter().enumerate() {
+            let (vd_viewport, scissor) = viewport.to_vd(&extent);
+            cmd_buffer.set_viewport(0, &[vd_viewport]);
+            cmd_buffer.set_scissor(0, &[scissor]);
 
-                instance += 1;
+            // Later viewports may overlap earlier ones (picture-in-picture)
+            if v > 0 {
+                let attachments = [
+                    vd::ClearAttachment::builder()
+                        .aspect_mask(vd::ImageAspectFlags::COLOR)
+                        .color_attachment(0)
+                        .clear_value(clears[0])
+                        .build(),
+                    vd::ClearAttachment::builder()
+                        .aspect_mask(vd::ImageAspectFlags::DEPTH)
+                        .clear_value(clears[1])
+                        .build(),
+                ];
+
+                let rect = vd::ClearRect::builder()
+                    .rect(scissor)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build();
+
+                cmd_buffer.clear_attachments(&attachments, &[rect]);
+            }
 
-                // Skip drawing hidden instances
-                if instances.data[j][k].1.hide { continue; }
+            let mut instance = 0;
+            for j in 0..self.models.len() {
+                // Render each instance
+                for _ in 0..instances.data[j].len() {
+                    // Bind uniform data
+                    cmd_buffer.bind_descriptor_sets(
+                        vd::PipelineBindPoint::Graphics,
+                        &self.pipeline_layout,
+                        0,
+                        &[&self.descriptor_sets[0]], // Single descriptor set
+                        // Offset shared and dynamic uniform buffers
+                        &[
+                            self.shared_alignment as u32 * v as u32,
+                            self.ubo_alignment as u32 * instance as u32,
+                        ],
+                    );
 
-                // Draw call
-                cmd_buffer.draw_indexed(
-                    self.models[j].index_count,
-                    1,
-                    self.models[j].index_offset,
-                    self.models[j].vertex_offset,
-                    0,
-                );
+                    instance += 1;
+
+                    // Skip drawing hidden and culled instances
+                    if !visible.next().unwrap() { continue; }
+
+                    // Draw call
+                    cmd_buffer.draw_indexed(
+                        self.models[j].index_count,
+                        1,
+                        self.models[j].index_offset,
+                        self.models[j].vertex_offset,
+                        0,
+                    );
+                }
             }
         }
 
@@ -728,6 +830,14 @@ impl<'a> Context<'a> {
             &mut self.text_display.text_instances,
         );
 
+        // 3D text and debug lines are projected with the main camera
+        let (main_viewport, main_scissor) = self.viewports.first()
+            .map_or(Viewport::full(), |viewport| *viewport)
+            .to_vd(&extent);
+
+        cmd_buffer.set_viewport(0, &[main_viewport.clone()]);
+        cmd_buffer.set_scissor(0, &[main_scissor.clone()]);
+
         self.text_display.end_text_update(
             &cmd_buffer,
             self.font_alignment,
@@ -765,6 +875,10 @@ impl<'a> Context<'a> {
             &mut self.label_display.text_instances,
         );
 
+        let (vd_viewport, scissor) = Viewport::full().to_vd(&extent);
+        cmd_buffer.set_viewport(0, &[vd_viewport]);
+        cmd_buffer.set_scissor(0, &[scissor]);
+
         self.label_display.end_text_update(
             &cmd_buffer,
             self.font_alignment,
@@ -780,12 +894,15 @@ impl<'a> Context<'a> {
                     &self.debug_data.as_ref().unwrap().pipeline.handle(),
                 );
 
+                cmd_buffer.set_viewport(0, &[main_viewport]);
+                cmd_buffer.set_scissor(0, &[main_scissor]);
+
                 cmd_buffer.bind_descriptor_sets(
                     vd::PipelineBindPoint::Graphics,
                     &self.pipeline_layout,
                     0,
                     &[&self.descriptor_sets[0]], // Single descriptor set
-                    &[0], // Ignore the dynamic uniform buffer
+                    &[0, 0], // First viewport; ignore the instance buffer
                 );
 
                 unsafe {
@@ -1047,6 +1164,8 @@ pub struct Model {
     index_offset: u32,
     vertex_count: usize,
     vertex_offset: i32,
+    center: alg::Vec3, // Bounding sphere, in model space
+    radius: f32,
 }
 
 impl Model {
@@ -1055,20 +1174,85 @@ impl Model {
         index_offset: u32,
         vertex_count: usize,
         vertex_offset: i32,
+        vertices: &[Vertex],
     ) -> Model {
+        // Sphere around the center of the bounding box
+        let center = if vertices.is_empty() {
+            alg::Vec3::zero()
+        } else {
+            let mut min = vertices[0].position;
+            let mut max = vertices[0].position;
+
+            for vertex in vertices {
+                let p = vertex.position;
+
+                min.x = min.x.min(p.x);
+                min.y = min.y.min(p.y);
+                min.z = min.z.min(p.z);
+
+                max.x = max.x.max(p.x);
+                max.y = max.y.max(p.y);
+                max.z = max.z.max(p.z);
+            }
+
+            (min + max) * 0.5
+        };
+
+        let radius = vertices.iter()
+            .map(|vertex| vertex.position.dist_squared(center))
+            .fold(0.0, f32::max)
+            .sqrt();
+
         Model {
             index_count,
             index_offset,
             vertex_count,
             vertex_offset,
+            center,
+            radius,
         }
     }
+
+    /// Test the bounding sphere, grown by `extent` (model space),
+    /// against a worldspace frustum
+    fn visible(
+        &self,
+        frustum: &alg::Frustum,
+        model: alg::Mat4,
+        extent: f32,
+    ) -> bool {
+        // Largest axis scale, for non-uniformly scaled models
+        let scale = alg::Vec3::new(model.x0, model.y0, model.z0).mag()
+            .max(alg::Vec3::new(model.x1, model.y1, model.z1).mag())
+            .max(alg::Vec3::new(model.x2, model.y2, model.z2).mag());
+
+        frustum.intersects_sphere(
+            model * self.center,
+            (self.radius + extent) * scale,
+        )
+    }
+}
+
+macro_rules! debug_validate_instance_handle {
+    ($self: ident, $handle: expr) => {
+        debug_assert!(
+            $self.live($handle),
+            "Instance {} was used after removal",
+            $handle,
+        );
+    }
 }
 
-/// Dynamic collection of instance data
+/// Dynamic collection of instance data. \
+/// Removed slots are hidden and kept on a per-model free list, so that
+/// handles to the remaining instances stay valid. Each slot has a
+/// generation, bumped on removal, to catch handles that outlived it.
 pub struct Instances {
     names: fnv::FnvHashMap<String, usize>,
+    model_names: Vec<String>, // Reverse lookup
     data: Vec<Vec<(InstanceUBO, InstanceMeta)>>,
+    generations: Vec<Vec<u32>>,
+    free: Vec<Vec<usize>>,
 }
 
 impl Instances {
@@ -1104,7 +1288,16 @@ impl Instances {
             names.insert(name.clone(), i);
         };
 
-        Instances { names, data }
+        let generations = (0..model_count).map(|_| Vec::new()).collect();
+        let free = (0..model_count).map(|_| Vec::new()).collect();
+
+        Instances {
+            names,
+            model_names: model_names.clone(),
+            data,
+            generations,
+            free,
+        }
     }
 
     /// Returns model index for given input string
@@ -1113,6 +1306,17 @@ impl Instances {
             .expect(&format!("Model \"{}\" does not exist", name))
     }
 
+    /// Returns true if a model with the given name exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    /// Returns model name for given model index
+    pub fn get_name(&self, index: usize) -> &str {
+        self.model_names.get(index)
+            .expect(&format!("Model index {} does not exist", index))
+    }
+
     /// Returns handle to new instance
     pub fn add(
         &mut self,
@@ -1121,16 +1325,60 @@ impl Instances {
     ) -> InstanceHandle {
         debug_assert!(model_index < self.data.len());
 
-        self.data[model_index].push(
-            (instance_data, InstanceMeta::default())
-        );
+        let entry = (instance_data, InstanceMeta::default());
+
+        // Reuse a removed slot if possible
+        let i = match self.free[model_index].pop() {
+            Some(i) => {
+                self.data[model_index][i] = entry;
+                i
+            },
+
+            None => {
+                self.data[model_index].push(entry);
+                self.generations[model_index].push(0);
+                self.data[model_index].len() - 1
+            },
+        };
 
         InstanceHandle::new(
             model_index as u32,
-            (self.data[model_index].len() - 1) as u32,
+            i as u32,
+            self.generations[model_index][i],
         )
     }
 
+    /// Returns true if the handle refers to an instance that has not been
+    /// removed
+    pub fn live(&self, handle: InstanceHandle) -> bool {
+        let (m, i) = (
+            handle.model_index() as usize,
+            handle.instance_index() as usize,
+        );
+
+        self.generations[m].get(i) == Some(&handle.generation)
+    }
+
+    /// Remove an instance, freeing its slot for reuse. \
+    /// The handle must not be used afterwards; removing it again is a bug,
+    /// caught in debug builds and ignored otherwise.
+    pub fn remove(&mut self, handle: InstanceHandle) {
+        // Freeing the slot twice would hand it out to two instances
+        if !self.live(handle) {
+            debug_assert!(false, "Instance {} was removed twice", handle);
+            return; // NOOP
+        }
+
+        let (m, i) = (
+            handle.model_index() as usize,
+            handle.instance_index() as usize,
+        );
+
+        self.data[m][i] = (InstanceUBO::default(), InstanceMeta::new(true));
+        self.generations[m][i] = self.generations[m][i].wrapping_add(1);
+        self.free[m].push(i);
+    }
+
     /// Modify data for an existing instance
     pub fn update(
         &mut self,
@@ -1142,6 +1390,7 @@ impl Instances {
             handle.instance_index() as usize,
         );
 
+        debug_validate_instance_handle!(self, handle);
         self.data[m][i].0 = ubo;
     }
 
@@ -1152,10 +1401,22 @@ impl Instances {
             handle.instance_index() as usize,
         );
 
+        debug_validate_instance_handle!(self, handle);
         self.data[m][i].1 = meta;
     }
 
-    /// Count instances (linear time)
+    /// Set how far vertex offsets reach beyond the model bounds
+    pub fn update_extent(&mut self, handle: InstanceHandle, extent: f32) {
+        let (m, i) = (
+            handle.model_index() as usize,
+            handle.instance_index() as usize,
+        );
+
+        debug_validate_instance_handle!(self, handle);
+        self.data[m][i].1.extent = extent;
+    }
+
+    /// Count instance slots, including removed ones (linear time)
     pub fn count(&self) -> usize {
         let mut count = 0;
 
@@ -1165,24 +1426,35 @@ impl Instances {
 
         count
     }
+
+    /// Count live instances (linear time)
+    pub fn live_count(&self) -> usize {
+        self.count() - self.free.iter().map(|free| free.len()).sum::<usize>()
+    }
 }
 
 #[derive(Clone, Copy)]
 pub struct InstanceHandle {
     _value: u32,
+    generation: u32, // Slot generation when the instance was added
 }
 
 impl InstanceHandle {
-    fn new(model_index: u32, instance_index: u32) -> InstanceHandle {
+    fn new(
+        model_index: u32,
+        instance_index: u32,
+        generation: u32,
+    ) -> InstanceHandle {
         debug_assert!(model_index >> 16 == 0);
         debug_assert!(instance_index >> 16 == 0);
 
         InstanceHandle {
-            _value: instance_index | (model_index << 16)
+            _value: instance_index | (model_index << 16),
+            generation,
         }
     }
 
-    fn model_index(self) -> u16 {
+    pub(crate) fn model_index(self) -> u16 {
         (self._value >> 16) as u16
     }
 
@@ -1212,17 +1484,18 @@ impl std::fmt::Debug for InstanceHandle {
 #[derive(Clone, Copy)]
 pub struct InstanceMeta {
     hide: bool,
+    extent: f32, // Furthest softbody offset, for culling
 }
 
 impl InstanceMeta {
     pub fn new(hide: bool) -> InstanceMeta {
-        InstanceMeta { hide }
+        InstanceMeta { hide, extent: 0.0 }
     }
 }
 
 impl Default for InstanceMeta {
     fn default() -> InstanceMeta {
-        InstanceMeta { hide: false }
+        InstanceMeta::new(false)
     }
 }
 
@@ -1382,6 +1655,71 @@ impl SharedUBO {
             projection,
         }
     }
+
+    pub fn view(&self) -> alg::Mat4 {
+        self.view
+    }
+
+    pub fn projection(&self) -> alg::Mat4 {
+        self.projection
+    }
+}
+
+/// Screen rectangle a camera renders into, normalized from the top left
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        debug_assert!(width > 0.0 && height > 0.0);
+        debug_assert!(x >= 0.0 && x + width <= 1.0);
+        debug_assert!(y >= 0.0 && y + height <= 1.0);
+
+        Viewport { x, y, width, height }
+    }
+
+    pub fn full() -> Viewport {
+        Viewport::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    /// Width over height, in pixels
+    pub fn aspect(self, screen: ::ScreenData) -> f32 {
+        (self.width * screen.width as f32)
+            / (self.height * screen.height as f32)
+    }
+
+    fn to_vd(self, extent: &vd::Extent2d) -> (vd::Viewport, vd::Rect2d) {
+        let (width, height) = (extent.width() as f32, extent.height() as f32);
+
+        let viewport = vd::Viewport::builder()
+            .x(self.x * width)
+            .y(self.y * height)
+            .width(self.width * width)
+            .height(self.height * height)
+            .min_depth(0f32)
+            .max_depth(1f32)
+            .build();
+
+        let scissor = vd::Rect2d::builder()
+            .offset(
+                vd::Offset2d::builder()
+                    .x((self.x * width) as i32)
+                    .y((self.y * height) as i32)
+                    .build()
+            ).extent(
+                vd::Extent2d::builder()
+                    .width((self.width * width) as u32)
+                    .height((self.height * height) as u32)
+                    .build()
+            ).build();
+
+        (viewport, scissor)
+    }
 }
 
 /// Uniform data sent to each individual instance
@@ -1389,6 +1727,7 @@ impl SharedUBO {
 #[repr(C)]
 pub struct InstanceUBO {
     model: alg::Mat4,
+    normal: alg::Mat4, // Inverse transpose of the model rotation and scale
     lights: [Light; MAX_INSTANCE_LIGHTS],
     position_offsets: [PaddedVec3; MAX_SOFTBODY_VERT],
     normal_offsets: [PaddedVec3; MAX_SOFTBODY_VERT],
@@ -1407,18 +1746,28 @@ impl InstanceUBO {
     ) -> InstanceUBO {
         InstanceUBO {
             model,
+            normal: normal_matrix(model),
             lights,
             position_offsets,
             normal_offsets,
             base_vertex: 0, // Set internally
         }
     }
+
+    /// Length of the largest position offset
+    pub fn offset_extent(&self) -> f32 {
+        self.position_offsets.iter()
+            .map(|offset| offset.value.mag_squared())
+            .fold(0.0, f32::max)
+            .sqrt()
+    }
 }
 
 impl Default for InstanceUBO {
     fn default() -> InstanceUBO {
         InstanceUBO {
             model: alg::Mat4::id(),
+            normal: alg::Mat4::id(),
             lights: [Light::default(); MAX_INSTANCE_LIGHTS],
             position_offsets: [PaddedVec3::default(); MAX_SOFTBODY_VERT],
             normal_offsets: [PaddedVec3::default(); MAX_SOFTBODY_VERT],
@@ -1427,6 +1776,26 @@ impl Default for InstanceUBO {
     }
 }
 
+// Keeps normals correct under non-uniform scale.
+// Computed once per instance rather than per vertex.
+fn normal_matrix(model: alg::Mat4) -> alg::Mat4 {
+    let linear = model.to_mat3();
+
+    // Degenerate (e.g. zero) scale has no inverse
+    if linear.det().abs() < std::f32::EPSILON {
+        return alg::Mat4::id();
+    }
+
+    let n = linear.inverse().transpose();
+
+    alg::Mat4::new(
+        n.x0, n.x1, n.x2, 0.0,
+        n.y0, n.y1, n.y2, 0.0,
+        n.z0, n.z1, n.z2, 0.0,
+         0.0,  0.0,  0.0, 1.0,
+    )
+}
+
 /// Uniform data sent to each individual font instance
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
@@ -1755,20 +2124,15 @@ fn get_swapchain_details(
 }
 
 /// Load base vertex and fragment shaders
-fn load_shaders<'a>(device: vd::Device) -> vd::Result<(
+fn load_shaders<'a>(
+    device: vd::Device,
+    shader_path: &str,
+) -> vd::Result<(
     vd::ShaderModule,
     vd::ShaderModule,
     [vd::PipelineShaderStageCreateInfo<'a>; 2],
 )> {
-    let path = {
-        let path = &config::load_section_setting::<String>(
-            &config::ENGINE_CONFIG,
-            "settings",
-            "shader_path"
-        );
-
-        [path, "/"].concat()
-    };
+    let path = [shader_path, "/"].concat();
 
     println!("Loading shaders from \"{}\"", path);
 
@@ -1850,19 +2214,17 @@ fn load_models(
         let mut vertex_offset = 0;
 
         for mut data in model_data {
-            let vertex_count = data.vertices.len();
-            vertices.append(&mut data.vertices); // Destructive
-
-            let index_count = data.indices.len() as u32;
-            indices.append(&mut data.indices); // Destructive
-
             let model = Model::new(
-                index_count,
+                data.indices.len() as u32,
                 index_offset,
-                vertex_count,
+                data.vertices.len(),
                 vertex_offset,
+                &data.vertices,
             );
 
+            vertices.append(&mut data.vertices); // Destructive
+            indices.append(&mut data.indices); // Destructive
+
             index_offset += model.index_count;
             vertex_offset += model.vertex_count as i32;
 
@@ -1914,6 +2276,7 @@ fn init_debug(
     render_pass: &vd::RenderPass,
     pipeline_layout: &vd::PipelineLayout,
     device: &vd::Device,
+    shader_path: &str,
 ) -> vd::Result<Option<DebugData>> { Ok(None) }
 
 #[cfg(debug_assertions)]
@@ -1922,6 +2285,7 @@ fn init_debug(
     render_pass: &vd::RenderPass,
     pipeline_layout: &vd::PipelineLayout,
     device: &vd::Device,
+    shader_path: &str,
 ) -> vd::Result<Option<DebugData>> {
     let properties = device.physical_device().memory_properties();
 
@@ -1936,15 +2300,7 @@ fn init_debug(
 
     /* Load debug shaders */
 
-    let path = {
-        let path = &config::load_section_setting::<String>(
-            &config::ENGINE_CONFIG,
-            "settings",
-            "shader_path"
-        );
-
-        [path, "/"].concat()
-    };
+    let path = [shader_path, "/"].concat();
 
     println!("Loading debug shaders from \"{}\"", path);
 
@@ -2058,11 +2414,22 @@ fn init_debug(
         .scissors(&scissors)
         .build();
 
+    // Set to the main camera's viewport when drawing
+    let dynamic_states = [
+        vd::DynamicState::Viewport,
+        vd::DynamicState::Scissor,
+    ];
+
+    let dynamic_state = vd::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(&dynamic_states)
+        .build();
+
     let pipeline = vd::GraphicsPipeline::builder()
         .stages(&[vert_stage, frag_stage])
         .vertex_input_state(&vert_info)
         .input_assembly_state(&assembly)
         .viewport_state(&viewport_state)
+        .dynamic_state(&dynamic_state)
         .rasterization_state(&rasterizer)
         .multisample_state(&multisampling)
         .color_blend_state(&blending)
@@ -2155,10 +2522,10 @@ fn init_fixed<'a>(device: vd::Device) -> vd::Result<(
     /* Descriptor set layout */
 
     let ubo_layout = {
-        // Shared UBO, sent to vertex shader
+        // Shared UBOs (one per viewport), sent to vertex shader
         let shared_binding = vd::DescriptorSetLayoutBinding::builder()
             .binding(0) // First binding
-            .descriptor_type(vd::DescriptorType::UniformBuffer)
+            .descriptor_type(vd::DescriptorType::UniformBufferDynamic)
             .descriptor_count(1) // Single descriptor (UBO)
             .stage_flags(vd::ShaderStageFlags::VERTEX)
             .build();
@@ -2489,6 +2856,16 @@ fn init_pipeline(
         .scissors(&scissors)
         .build();
 
+    // Set per camera when drawing
+    let dynamic_states = [
+        vd::DynamicState::Viewport,
+        vd::DynamicState::Scissor,
+    ];
+
+    let dynamic_state = vd::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(&dynamic_states)
+        .build();
+
     /* Pipeline */
 
     Ok(
@@ -2497,6 +2874,7 @@ fn init_pipeline(
         .vertex_input_state(&vert_info)
         .input_assembly_state(assembly)
         .viewport_state(&viewport_state)
+        .dynamic_state(&dynamic_state)
         .rasterization_state(rasterizer)
         .multisample_state(multisampling)
         .color_blend_state(&blending)
@@ -2668,17 +3046,13 @@ fn init_drawing(
     /* Uniform buffers */
 
     let pool_sizes = {
-        let size = vd::DescriptorPoolSize::builder()
-            .type_of(vd::DescriptorType::UniformBuffer)
-            .descriptor_count(1) // Shared by all models
-            .build();
-
+        // Shared and instance UBOs are both offset at draw time
         let dynamic_size = vd::DescriptorPoolSize::builder()
             .type_of(vd::DescriptorType::UniformBufferDynamic)
-            .descriptor_count(1) // Shared by all models
+            .descriptor_count(2) // Shared by all models
             .build();
 
-        [size, dynamic_size]
+        [dynamic_size]
     };
 
     let descriptor_pool = vd::DescriptorPool::builder()
@@ -2715,9 +3089,9 @@ fn init_drawing(
         std::mem::size_of::<SharedUBO>() as u64
     );
 
-    // Allocate a buffer for the shared UBO
+    // Allocate a buffer for the shared UBOs
     let (ubo_buffer, ubo_memory) = create_buffer(
-        shared_alignment, // Contains single UBO
+        MAX_VIEWPORTS as u64 * shared_alignment, // One UBO per viewport
         vd::BufferUsageFlags::UNIFORM_BUFFER,
         device,
           vd::MemoryPropertyFlags::HOST_VISIBLE
@@ -2769,7 +3143,7 @@ fn init_drawing(
             .dst_binding(0) // First binding
             .dst_array_element(0)
             .descriptor_count(1)
-            .descriptor_type(vd::DescriptorType::UniformBuffer)
+            .descriptor_type(vd::DescriptorType::UniformBufferDynamic)
             .buffer_info(&shared_info)
             .build(),
         vd::WriteDescriptorSet::builder()
@@ -3130,7 +3504,7 @@ fn set_image_layout_helper(
     );
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub enum TextAlign { Left, Center, Right }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -3472,7 +3846,7 @@ fn init_text_pipeline_builder(
     )
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum TextScale {
     Pixel,
     Aspect,
@@ -3552,6 +3926,7 @@ fn create_text(
     render_pass: &vd::RenderPass,
     text_meta: &TextMeta,
     is_2d: bool,
+    shader_path: &str,
 ) -> vd::Result<TextDisplay> {
     let (binding_description, attribute_descriptions) =
         if is_2d {
@@ -3563,15 +3938,7 @@ fn create_text(
         };
 
     // Load shaders
-    let path = {
-        let path = &config::load_section_setting::<String>(
-            &config::ENGINE_CONFIG,
-            "settings",
-            "shader_path"
-        );
-
-        [path, "/"].concat()
-    };
+    let path = [shader_path, "/"].concat();
 
     let shader_type = if is_2d {'2'} else {'3'};
 
@@ -3632,6 +3999,16 @@ fn create_text(
         .scissors(&text_meta.scissors)
         .build();
 
+    // Set per text kind when drawing (labels cover the whole screen)
+    let dynamic_states = [
+        vd::DynamicState::Viewport,
+        vd::DynamicState::Scissor,
+    ];
+
+    let dynamic_state = vd::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(&dynamic_states)
+        .build();
+
     let rasterizer = vd::PipelineRasterizationStateCreateInfo::builder()
         .depth_clamp_enable(false)
         .rasterizer_discard_enable(false)
@@ -3822,6 +4199,7 @@ fn create_text(
         .vertex_input_state(&vert_info)
         .input_assembly_state(&assembly)
         .viewport_state(&viewport_state)
+        .dynamic_state(&dynamic_state)
         .rasterization_state(&rasterizer)
         .multisample_state(&multisampling)
         .color_blend_state(&blending)
@@ -3960,3 +4338,91 @@ impl TextDisplay {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use render::*;
+
+    #[test]
+    fn normal_matrix_scale() {
+        let model = alg::Mat4::new(
+            2.0, 0.0, 0.0, 5.0,
+            0.0, 1.0, 0.0, 6.0,
+            0.0, 0.0, 4.0, 7.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+
+        // Translation is dropped and scale is inverted
+        assert!(
+            normal_matrix(model) == alg::Mat4::new(
+                0.5, 0.0,  0.0, 0.0,
+                0.0, 1.0,  0.0, 0.0,
+                0.0, 0.0, 0.25, 0.0,
+                0.0, 0.0,  0.0, 1.0,
+            )
+        );
+
+        // Zero scale falls back to identity instead of panicking
+        let zero = alg::Mat4::translation(1.0, 2.0, 3.0) * alg::Mat4::new(
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+
+        assert!(normal_matrix(zero) == alg::Mat4::id());
+    }
+
+    #[test]
+    fn reuse_instance_slot() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let mut instances = Instances::new(2, &names, None);
+
+        let first = instances.add(InstanceUBO::default(), 1);
+        let second = instances.add(InstanceUBO::default(), 1);
+        instances.remove(first);
+
+        assert!(instances.count() == 2);
+        assert!(instances.live_count() == 1);
+        assert!(instances.data[1][0].1.hide);
+
+        // Removed slot is filled before the model grows
+        let third = instances.add(InstanceUBO::default(), 1);
+        assert!(third.model_index() == 1);
+        assert!(third.instance_index() == first.instance_index());
+        assert!(!instances.data[1][0].1.hide);
+        assert!(instances.count() == 2);
+        assert!(instances.live_count() == 2);
+
+        // Handles to a reused slot are told apart by generation
+        assert!(!instances.live(first));
+        assert!(instances.live(third));
+
+        instances.remove(second);
+        let fourth = instances.add(InstanceUBO::default(), 1);
+        let fifth = instances.add(InstanceUBO::default(), 1);
+        assert!(fourth.instance_index() != fifth.instance_index());
+        assert!(instances.live(fourth) && !instances.live(second));
+
+        // Other models are untouched
+        instances.remove(fourth);
+        let other = instances.add(InstanceUBO::default(), 0);
+        assert!(other.model_index() == 0);
+        assert!(instances.count() == 4);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn remove_stale_instance() {
+        let names = vec!["a".to_string()];
+        let mut instances = Instances::new(1, &names, None);
+
+        let first = instances.add(InstanceUBO::default(), 0);
+        instances.remove(first);
+        instances.add(InstanceUBO::default(), 0);
+
+        // Would free the slot now owned by the second instance
+        instances.remove(first);
+    }
+}
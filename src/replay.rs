@@ -0,0 +1,329 @@
+use std;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::fs::File;
+
+use alg;
+use input;
+
+const HEADER: &str = "nmg-replay 2";
+
+/* Replay files are plain text. The header is followed by the timing the
+ * recording was made with, since playback is only deterministic under the
+ * same fixed step:
+ * fixed_dt fixed_step_factor
+ * and then one frame per line:
+ * delta keys cursor.x cursor.y mouse.x mouse.y
+ *     joy_l.x joy_l.y joy_r.x joy_r.y trig_l trig_r
+ * where `keys` is a bitmask of pressed keys (indexed by `input::Key`).
+ * Floats are written with Rust's shortest round-trip representation,
+ * so playback is bit-exact.
+ */
+
+fn encode(snapshot: &input::Snapshot) -> String {
+    let mut keys = 0u32;
+
+    for (i, pressed) in snapshot.keys.iter().enumerate() {
+        if *pressed { keys |= 1 << i; }
+    }
+
+    format!(
+        "{} {} {} {} {} {} {} {} {} {} {} {}",
+        snapshot.delta,
+        keys,
+        snapshot.cursor_coords.x, snapshot.cursor_coords.y,
+        snapshot.mouse_delta.x, snapshot.mouse_delta.y,
+        snapshot.joy_l.x, snapshot.joy_l.y,
+        snapshot.joy_r.x, snapshot.joy_r.y,
+        snapshot.trig_l,
+        snapshot.trig_r,
+    )
+}
+
+fn decode(line: &str) -> Option<input::Snapshot> {
+    let mut iter = line.split_whitespace();
+
+    let delta = iter.next()?.parse::<f64>().ok()?;
+    let mask = iter.next()?.parse::<u32>().ok()?;
+
+    let mut values = [0f32; 10];
+    for value in &mut values {
+        *value = iter.next()?.parse::<f32>().ok()?;
+    }
+
+    // Trailing data is malformed
+    if iter.next().is_some() { return None; }
+
+    let mut keys = [false; input::KEY_COUNT];
+    for (i, key) in keys.iter_mut().enumerate() {
+        *key = mask & (1 << i) != 0;
+    }
+
+    Some(
+        input::Snapshot {
+            delta,
+            keys,
+            cursor_coords: alg::Vec2::new(values[0], values[1]),
+            mouse_delta: alg::Vec2::new(values[2], values[3]),
+            joy_l: alg::Vec2::new(values[4], values[5]),
+            joy_r: alg::Vec2::new(values[6], values[7]),
+            trig_l: values[8],
+            trig_r: values[9],
+        }
+    )
+}
+
+fn decode_timing(line: &str) -> Option<(f32, f32)> {
+    let mut iter = line.split_whitespace();
+
+    let fixed_dt = iter.next()?.parse::<f32>().ok()?;
+    let fixed_step_factor = iter.next()?.parse::<f32>().ok()?;
+
+    if iter.next().is_some() || fixed_dt <= 0.0 || fixed_step_factor <= 0.0 {
+        return None;
+    }
+
+    Some((fixed_dt, fixed_step_factor))
+}
+
+/// Writes input snapshots to a replay file, one per frame
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn new(
+        path: &str,
+        fixed_dt: f32,
+        fixed_step_factor: f32,
+    ) -> std::io::Result<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", HEADER)?;
+        writeln!(writer, "{} {}", fixed_dt, fixed_step_factor)?;
+
+        Ok(Recorder { writer })
+    }
+
+    pub fn record(&mut self, snapshot: &input::Snapshot) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", encode(snapshot))
+    }
+
+    /// Flush buffered frames to disk
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads a replay file and hands out its snapshots in order
+pub struct Player {
+    frames: Vec<input::Snapshot>,
+    index: usize,
+    fixed_dt: f32,
+    fixed_step_factor: f32,
+}
+
+impl Player {
+    pub fn load(path: &str) -> std::io::Result<Player> {
+        let file = BufReader::new(File::open(path)?);
+        let mut lines = file.lines();
+
+        let invalid = |message: String| std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            message,
+        );
+
+        match lines.next() {
+            Some(line) => if line? != HEADER {
+                return Err(invalid(
+                    format!("\"{}\" is not a replay file", path)
+                ));
+            },
+
+            None => return Err(invalid(format!("\"{}\" is empty", path))),
+        }
+
+        let timing = match lines.next() {
+            Some(line) => decode_timing(&line?),
+            None => None,
+        };
+
+        let (fixed_dt, fixed_step_factor) = timing.ok_or_else(
+            || invalid(format!("Missing fixed timestep in \"{}\"", path))
+        )?;
+
+        let mut frames = Vec::new();
+
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+
+            let snapshot = decode(&line).ok_or_else(
+                || invalid(format!("Malformed frame {} in \"{}\"", i, path))
+            )?;
+
+            frames.push(snapshot);
+        }
+
+        Ok(Player::new(frames, fixed_dt, fixed_step_factor))
+    }
+
+    pub fn new(
+        frames: Vec<input::Snapshot>,
+        fixed_dt: f32,
+        fixed_step_factor: f32,
+    ) -> Player {
+        Player {
+            frames,
+            index: 0,
+            fixed_dt,
+            fixed_step_factor,
+        }
+    }
+
+    /// Fixed timestep the recording was made with
+    pub fn fixed_dt(&self) -> f32 {
+        self.fixed_dt
+    }
+
+    pub fn fixed_step_factor(&self) -> f32 {
+        self.fixed_step_factor
+    }
+
+    pub fn finished(&self) -> bool {
+        self.index >= self.frames.len()
+    }
+
+    /// Total number of recorded frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl Iterator for Player {
+    type Item = input::Snapshot;
+
+    /// Returns the next recorded frame, or `None` if playback is finished
+    fn next(&mut self) -> Option<input::Snapshot> {
+        let snapshot = self.frames.get(self.index).cloned();
+
+        if snapshot.is_some() {
+            self.index += 1;
+        }
+
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use input;
+    use headless;
+    use components::Component;
+    use replay::*;
+
+    #[test]
+    fn encode_decode() {
+        let mut keys = [false; input::KEY_COUNT];
+        keys[input::Key::W as usize] = true;
+        keys[input::Key::West as usize] = true;
+
+        let snapshot = input::Snapshot {
+            delta: 1.0 / 144.0,
+            keys,
+            cursor_coords: alg::Vec2::new(640.5, 360.25),
+            mouse_delta: alg::Vec2::new(-3.0, 0.1),
+            joy_l: alg::Vec2::new(0.3333333, -1.0),
+            joy_r: alg::Vec2::zero(),
+            trig_l: 0.7,
+            trig_r: 1e-7,
+        };
+
+        let decoded = decode(&encode(&snapshot)).unwrap();
+        assert!(decoded == snapshot);
+    }
+
+    #[test]
+    fn decode_malformed() {
+        assert!(decode("").is_none());
+        assert!(decode("0.1 0 0 0").is_none());
+        assert!(decode("0.1 0 0 0 0 0 0 0 0 0 0 0 0").is_none());
+
+        assert!(decode_timing("0.01 1") == Some((0.01, 1.0)));
+        assert!(decode_timing("0.01").is_none());
+        assert!(decode_timing("0 1").is_none());
+    }
+
+    // Fly camera and a falling box, so both input and physics are replayed
+    fn runner() -> (headless::Runner<headless::Empty>, Vec<entity::Handle>) {
+        let mut runner = headless::Runner::new(vec![], headless::Empty);
+
+        let camera = runner.entities.add();
+        runner.components.transforms.register(camera);
+        runner.components.controllers.register(camera);
+        runner.components.controllers.build_fly().for_entity(camera);
+
+        let body = runner.entities.add();
+        runner.components.transforms.register(body);
+        runner.components.softbodies.register(body);
+        runner.components.softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .initial_pos(alg::Vec3::new(0.0, 2.0, 0.0))
+            .for_entity(body);
+
+        (runner, vec![camera, body])
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join("nmg_record_and_replay");
+        let path = path.to_str().unwrap();
+
+        // Live run, with a timestep the replaying runner doesn't start with
+        let (mut live, entities) = runner();
+        live.set_fixed_step_factor(0.5);
+        live.set_fixed_dt(0.02);
+
+        let mut recorder = Recorder::new(path, 0.02, 0.5).unwrap();
+
+        for i in 0..64 {
+            live.set_key(input::Key::W, i % 3 != 0);
+            live.set_mouse_delta(alg::Vec2::new(i as f32 * 0.5, -1.0));
+
+            let delta = 0.004 + 0.001 * (i % 7) as f64;
+            recorder.record(&live.input.snapshot(delta)).unwrap();
+            live.step(delta);
+        }
+
+        recorder.flush().unwrap();
+
+        let mut player = Player::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(player.len() == 64);
+        assert!(player.fixed_dt() == 0.02);
+
+        let (mut replayed, _) = runner();
+        replayed.replay(&mut player);
+        assert!(player.finished());
+
+        for &entity in &entities {
+            let transforms = (
+                &live.components.transforms,
+                &replayed.components.transforms,
+            );
+
+            assert!(transforms.1.registered(entity));
+            assert!(
+                transforms.0.get_position(entity)
+                    == transforms.1.get_position(entity)
+            );
+            assert!(
+                transforms.0.get_orientation(entity)
+                    == transforms.1.get_orientation(entity)
+            );
+        }
+    }
+}
@@ -0,0 +1,1236 @@
+extern crate fnv;
+
+use std;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::fs::File;
+
+use alg;
+use graphics;
+use render;
+use entity;
+use components;
+
+use components::Component;
+use components::query::Query;
+use components::softbody::Source;
+use components::camera::Projection;
+
+const HEADER: &str = "nmg-scene 1";
+
+/* Scene files are plain text, one directive per line. Blank lines and
+ * lines starting with '#' are ignored. Each `entity` line starts a new
+ * entity; the directives that follow it apply to that entity:
+ *
+ * entity <id>
+ * name "<name>"
+ * tag "<tag>"
+ * transform px py pz qx qy qz qw sx sy sz (worldspace)
+ * parent <id>
+ * draw ["<model>"]
+ * light vx vy vz radius r g b intensity
+ * camera perspective|orthographic fov size near far vx vy vw vh [active n]
+ * label left|center|right pixel|aspect factor "<string>"
+ * text left|center|right factor "<string>"
+ * softbody box sx sy sz mass rigidity end_offset
+ * softbody model mass rigidity end_offset "<model>"
+ *
+ * Joints and softbody planes follow the entities:
+ *
+ * joint parent child xmin xmax ymin ymax zmin zmax unlocked
+ *     fx fy fz ux uy uz ox oy oz (degrees; unlocked is 0 or 1)
+ * plane nx ny nz offset
+ *
+ * Ids are local to the file (the entity's position in it). Active cameras
+ * store their position among the active ones (0 is the main camera); a
+ * scene with active cameras replaces the current selection on load.
+ * Quoted strings
+ * escape backslashes, quotes, newlines, carriage returns and tabs with a
+ * backslash, so that they fit on one line and keep surrounding spaces.
+ */
+
+/// Write the given entities and their components to a scene file. \
+/// Parents and joints that refer to entities outside of `handles` are
+/// omitted. Softbodies created from raw particles are saved without an
+/// instance. Softbody planes are not owned by any entity, so all of them
+/// are saved.
+pub fn save(
+    path: &str,
+    handles: &[entity::Handle],
+    entities: &entity::Manager,
+    components: &components::Container,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "{}", HEADER)?;
+
+    let ids = handles.iter().enumerate()
+        .map(|(i, handle)| (*handle, i))
+        .collect::<fnv::FnvHashMap<_, _>>();
+
+    for (id, handle) in handles.iter().enumerate() {
+        let entity = *handle;
+        writeln!(writer, "\nentity {}", id)?;
+
+        if let Some(name) = entities.get_name(entity) {
+            writeln!(writer, "name {}", encode_string(name))?;
+        }
+
+        for tag in entities.tags(entity) {
+            writeln!(writer, "tag {}", encode_string(tag))?;
+        }
+
+        if components.transforms.registered(entity) {
+            let (position, orientation, scale)
+                = components.transforms.get(entity);
+
+            writeln!(
+                writer,
+                "transform {} {} {} {} {} {} {} {} {} {}",
+                position.x, position.y, position.z,
+                orientation.x, orientation.y, orientation.z, orientation.w,
+                scale.x, scale.y, scale.z,
+            )?;
+
+            let parent = components.transforms.get_parent(entity)
+                .and_then(|parent| ids.get(&parent));
+
+            if let Some(parent) = parent {
+                writeln!(writer, "parent {}", parent)?;
+            }
+        }
+
+        if components.draws.registered(entity) {
+            match components.draws.get_model(entity) {
+                Some(model) => {
+                    writeln!(writer, "draw {}", encode_string(model))?
+                },
+                None => writeln!(writer, "draw")?,
+            }
+        }
+
+        if let Some(light) = components.lights.fetch(entity) {
+            writeln!(
+                writer,
+                "light {} {} {} {} {} {} {} {}",
+                light.vector.x, light.vector.y, light.vector.z,
+                light.radius,
+                light.color.r, light.color.g, light.color.b,
+                light.intensity,
+            )?;
+        }
+
+        if components.cameras.registered(entity) {
+            let viewport = components.cameras.get_viewport(entity);
+
+            write!(
+                writer,
+                "camera {} {} {} {} {} {} {} {} {}",
+                match components.cameras.get_projection(entity) {
+                    Projection::Perspective => "perspective",
+                    Projection::Orthographic => "orthographic",
+                },
+                components.cameras.get_fov(entity),
+                components.cameras.get_size(entity),
+                components.cameras.get_near(entity),
+                components.cameras.get_far(entity),
+                viewport.x, viewport.y, viewport.width, viewport.height,
+            )?;
+
+            match components.cameras.get_active(entity) {
+                Some(position) => writeln!(writer, " active {}", position)?,
+                None => writeln!(writer)?,
+            }
+        }
+
+        if let Some(label) = components.labels.fetch(entity) {
+            writeln!(
+                writer,
+                "label {} {} {} {}",
+                encode_align(label.align),
+                match label.scale {
+                    render::TextScale::Pixel => "pixel",
+                    render::TextScale::Aspect => "aspect",
+                },
+                label.scale_factor,
+                encode_string(&label.text),
+            )?;
+        }
+
+        if let Some(text) = components.texts.fetch(entity) {
+            writeln!(
+                writer,
+                "text {} {} {}",
+                encode_align(text.align),
+                text.scale_factor,
+                encode_string(&text.text),
+            )?;
+        }
+
+        if components.softbodies.registered(entity) {
+            match components.softbodies.fetch(entity) {
+                Some(instance) => match instance.source() {
+                    Source::BoxLimb(scale) => writeln!(
+                        writer,
+                        "softbody box {} {} {} {} {} {}",
+                        scale.x, scale.y, scale.z,
+                        instance.mass,
+                        instance.rigidity,
+                        instance.end_offset(),
+                    )?,
+
+                    Source::Model(model) => writeln!(
+                        writer,
+                        "softbody model {} {} {} {}",
+                        instance.mass,
+                        instance.rigidity,
+                        instance.end_offset(),
+                        encode_string(model),
+                    )?,
+
+                    Source::Custom => {
+                        eprintln!(
+                            "Warning: Custom softbody instance for entity {} \
+                            cannot be saved",
+                            entity,
+                        );
+
+                        writeln!(writer, "softbody")?;
+                    },
+                },
+
+                None => writeln!(writer, "softbody")?,
+            }
+        }
+    }
+
+    let joints = components.softbodies.joints();
+    if !joints.is_empty() { writeln!(writer)?; }
+
+    for joint in joints {
+        let (parent, child) = match (
+            ids.get(&joint.parent),
+            ids.get(&joint.child),
+        ) {
+            (Some(parent), Some(child)) => (parent, child),
+            _ => continue,
+        };
+
+        writeln!(
+            writer,
+            "joint {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            parent, child,
+            joint.x.0, joint.x.1,
+            joint.y.0, joint.y.1,
+            joint.z.0, joint.z.1,
+            joint.unlocked as u8,
+            joint.fwd.x, joint.fwd.y, joint.fwd.z,
+            joint.up.x, joint.up.y, joint.up.z,
+            joint.offset.x, joint.offset.y, joint.offset.z,
+        )?;
+    }
+
+    let planes = components.softbodies.planes();
+    if !planes.is_empty() { writeln!(writer)?; }
+
+    for plane in planes {
+        writeln!(
+            writer,
+            "plane {} {} {} {}",
+            plane.normal.x, plane.normal.y, plane.normal.z,
+            plane.offset,
+        )?;
+    }
+
+    writer.flush()
+}
+
+/// Create the entities described by a scene file, returning their
+/// handles in file order. \
+/// Planes that already exist (e.g. from a previously loaded scene) are
+/// not added again. \
+/// `models` is used to build softbody instances from meshes.
+pub fn load(
+    path: &str,
+    models: &[render::ModelData],
+    entities: &mut entity::Manager,
+    components: &mut components::Container,
+) -> std::io::Result<Vec<entity::Handle>> {
+    let file = BufReader::new(File::open(path)?);
+    let mut lines = file.lines();
+
+    let invalid = |message: String| std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} in \"{}\"", message, path),
+    );
+
+    match lines.next() {
+        Some(line) => if line? != HEADER {
+            return Err(invalid("Missing scene header".to_string()));
+        },
+
+        None => return Err(invalid("Empty scene".to_string())),
+    }
+
+    /* Parse */
+
+    let mut records: Vec<Record> = Vec::new();
+    let mut joints = Vec::new();
+    let mut planes = Vec::new();
+
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        // Header is line 1
+        let malformed = || invalid(format!("Malformed line {}", i + 2));
+
+        let (directive, rest) = match line.find(' ') {
+            Some(j) => (&line[..j], line[j + 1..].trim()),
+            None => (line, ""),
+        };
+
+        match directive {
+            "entity" => {
+                let id = rest.parse::<usize>().map_err(|_| malformed())?;
+                if id != records.len() { return Err(malformed()); }
+                records.push(Record::default());
+                continue;
+            },
+
+            "joint" => {
+                let values = parse_floats(rest, 18).ok_or_else(malformed)?;
+                let id = |value: f32| {
+                    if value >= 0.0 && value.fract() == 0.0 {
+                        Some(value as usize)
+                    } else { None }
+                };
+
+                joints.push(JointRecord {
+                    parent: id(values[0]).ok_or_else(malformed)?,
+                    child: id(values[1]).ok_or_else(malformed)?,
+                    x: (values[2], values[3]),
+                    y: (values[4], values[5]),
+                    z: (values[6], values[7]),
+                    unlocked: values[8] != 0.0,
+                    fwd: vec3(&values[9..12]),
+                    up: vec3(&values[12..15]),
+                    offset: vec3(&values[15..18]),
+                });
+
+                continue;
+            },
+
+            "plane" => {
+                let values = parse_floats(rest, 4).ok_or_else(malformed)?;
+                planes.push(alg::Plane::new(vec3(&values), values[3]));
+                continue;
+            },
+
+            _ => (),
+        }
+
+        // Remaining directives apply to the current entity
+        let record = records.last_mut().ok_or_else(malformed)?;
+
+        match directive {
+            "name" => {
+                let name = decode_string(rest).ok_or_else(malformed)?;
+                record.name = Some(name);
+            },
+
+            "tag" => {
+                let tag = decode_string(rest).ok_or_else(malformed)?;
+                record.tags.push(tag);
+            },
+
+            "transform" => {
+                let values = parse_floats(rest, 10).ok_or_else(malformed)?;
+
+                record.transform = Some((
+                    vec3(&values[0..3]),
+                    alg::Quat::new(values[3], values[4], values[5], values[6]),
+                    vec3(&values[7..10]),
+                ));
+            },
+
+            "parent" => {
+                let id = rest.parse::<usize>().map_err(|_| malformed())?;
+                record.parent = Some(id);
+            },
+
+            "draw" => {
+                record.draw = Some(if rest.is_empty() { None } else {
+                    Some(decode_string(rest).ok_or_else(malformed)?)
+                });
+            },
+
+            "light" => {
+                let values = parse_floats(rest, 8).ok_or_else(malformed)?;
+
+                record.light = Some(render::Light {
+                    vector: vec3(&values[0..3]),
+                    radius: values[3],
+                    color: graphics::Color::new(
+                        values[4],
+                        values[5],
+                        values[6],
+                    ),
+                    intensity: values[7],
+                });
+            },
+
+            "camera" => {
+                let mut iter = rest.splitn(2, ' ');
+
+                let projection = match iter.next() {
+                    Some("perspective") => Projection::Perspective,
+                    Some("orthographic") => Projection::Orthographic,
+                    _ => return Err(malformed()),
+                };
+
+                // Position among the active cameras is optional
+                let rest = iter.next().unwrap_or("");
+                let (rest, active) = match rest.find(" active ") {
+                    Some(i) => {
+                        let active = rest[i + 8..].trim().parse::<usize>()
+                            .map_err(|_| malformed())?;
+
+                        (&rest[..i], Some(active))
+                    },
+
+                    None => (rest, None),
+                };
+
+                let values = parse_floats(rest, 8).ok_or_else(malformed)?;
+                let (x, y, width, height)
+                    = (values[4], values[5], values[6], values[7]);
+
+                if values[1] <= 0.0
+                    || width <= 0.0 || height <= 0.0
+                    || x < 0.0 || x + width > 1.0
+                    || y < 0.0 || y + height > 1.0
+                {
+                    return Err(malformed());
+                }
+
+                record.camera = Some(CameraRecord {
+                    projection,
+                    fov: values[0],
+                    size: values[1],
+                    near: values[2],
+                    far: values[3],
+                    viewport: render::Viewport::new(x, y, width, height),
+                    active,
+                });
+            },
+
+            "label" => {
+                let mut iter = rest.splitn(4, ' ');
+
+                let align = iter.next().and_then(decode_align);
+                let scale = match iter.next() {
+                    Some("pixel") => Some(render::TextScale::Pixel),
+                    Some("aspect") => Some(render::TextScale::Aspect),
+                    _ => None,
+                };
+
+                let factor = iter.next().and_then(|s| s.parse::<f32>().ok());
+                let text = iter.next().and_then(decode_string);
+
+                match (align, scale, factor, text) {
+                    (Some(align), Some(scale), Some(factor), Some(text)) => {
+                        record.label = Some((align, scale, factor, text));
+                    },
+
+                    _ => return Err(malformed()),
+                }
+            },
+
+            "text" => {
+                let mut iter = rest.splitn(3, ' ');
+
+                let align = iter.next().and_then(decode_align);
+                let factor = iter.next().and_then(|s| s.parse::<f32>().ok());
+                let text = iter.next().and_then(decode_string);
+
+                match (align, factor, text) {
+                    (Some(align), Some(factor), Some(text)) => {
+                        record.text = Some((align, factor, text));
+                    },
+
+                    _ => return Err(malformed()),
+                }
+            },
+
+            "softbody" => {
+                let mut iter = rest.splitn(2, ' ');
+
+                record.softbody = Some(match iter.next() {
+                    Some("box") => {
+                        let values = parse_floats(iter.next().unwrap_or(""), 6)
+                            .ok_or_else(malformed)?;
+
+                        Some(SoftbodyRecord {
+                            source: Source::BoxLimb(vec3(&values[0..3])),
+                            mass: values[3],
+                            rigidity: values[4],
+                            end_offset: values[5],
+                        })
+                    },
+
+                    Some("model") => {
+                        let mut iter = iter.next().unwrap_or("").splitn(4, ' ');
+                        let mut values = [0f32; 3];
+
+                        for value in &mut values {
+                            *value = iter.next()
+                                .and_then(|s| s.parse::<f32>().ok())
+                                .ok_or_else(malformed)?;
+                        }
+
+                        let model = iter.next().and_then(decode_string)
+                            .ok_or_else(malformed)?;
+
+                        Some(SoftbodyRecord {
+                            source: Source::Model(model),
+                            mass: values[0],
+                            rigidity: values[1],
+                            end_offset: values[2],
+                        })
+                    },
+
+                    None | Some("") => None, // No instance
+                    _ => return Err(malformed()),
+                });
+            },
+
+            _ => return Err(invalid(
+                format!("Unknown directive \"{}\" on line {}", directive, i + 2)
+            )),
+        }
+    }
+
+    /* Validate references before creating anything */
+
+    for record in &records {
+        if let Some(parent) = record.parent {
+            if parent >= records.len() {
+                return Err(invalid(format!("Invalid parent id {}", parent)));
+            }
+        }
+
+        if let Some(Some(ref model)) = record.draw {
+            if !components.draws.instances.contains(model) {
+                return Err(invalid(format!("Unknown model \"{}\"", model)));
+            }
+        }
+
+        if let Some(Some(SoftbodyRecord {
+            source: Source::Model(ref model), ..
+        })) = record.softbody {
+            if !models.iter().any(|data| &data.name == model) {
+                return Err(invalid(format!("Unknown model \"{}\"", model)));
+            }
+        }
+    }
+
+    // Active cameras need distinct positions, one viewport each
+    let mut positions = records.iter()
+        .filter_map(|record| record.camera.as_ref()?.active)
+        .collect::<Vec<_>>();
+
+    let count = positions.len();
+    positions.sort();
+    positions.dedup();
+
+    if positions.len() != count || count > render::MAX_VIEWPORTS {
+        return Err(invalid("Invalid active cameras".to_string()));
+    }
+
+    for (id, record) in records.iter().enumerate() {
+        let has_transform = |id: usize| records[id].transform.is_some();
+
+        if record.softbody.is_some() && !has_transform(id) {
+            return Err(invalid(format!("Softbody {} has no transform", id)));
+        }
+
+        let parent = match record.parent {
+            Some(parent) => parent,
+            None => continue,
+        };
+
+        if parent == id {
+            return Err(invalid(format!("Entity {} is its own parent", id)));
+        }
+
+        if !has_transform(id) || !has_transform(parent) {
+            return Err(invalid(format!(
+                "Parent {} of entity {} requires transforms on both",
+                parent,
+                id,
+            )));
+        }
+
+        // A chain longer than the record count must loop
+        let mut ancestor = parent;
+        for _ in 0..records.len() {
+            ancestor = match records[ancestor].parent {
+                Some(next) if next == id => return Err(invalid(
+                    format!("Parent cycle through entity {}", id)
+                )),
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    for joint in &joints {
+        let valid = |id: usize| records.get(id)
+            .map_or(false, |record| match record.softbody {
+                Some(Some(_)) => true,
+                _ => false,
+            });
+
+        if !valid(joint.parent) || !valid(joint.child) {
+            return Err(invalid(format!(
+                "Invalid joint between {} and {}",
+                joint.parent,
+                joint.child,
+            )));
+        }
+    }
+
+    /* Create entities */
+
+    let mut handles = Vec::with_capacity(records.len());
+
+    for record in &records {
+        let entity = entities.add();
+
+        if let Some(ref name) = record.name {
+            entities.set_name(entity, name);
+        }
+
+        for tag in &record.tags {
+            entities.add_tag(entity, tag);
+        }
+
+        if let Some((position, orientation, scale)) = record.transform {
+            components.transforms.register(entity);
+            components.transforms.set(entity, position, orientation, scale);
+        }
+
+        if let Some(ref draw) = record.draw {
+            components.draws.register(entity);
+
+            if let Some(model) = draw {
+                components.draws.bind_model(entity, model);
+            }
+        }
+
+        if let Some(light) = record.light {
+            components.lights.register(entity);
+            components.lights.set(entity, light);
+        }
+
+        if let Some(ref camera) = record.camera {
+            components.cameras.register(entity);
+            components.cameras.set_projection(entity, camera.projection);
+            components.cameras.set_fov(entity, camera.fov);
+            components.cameras.set_size(entity, camera.size);
+            components.cameras.set_near(entity, camera.near);
+            components.cameras.set_far(entity, camera.far);
+            components.cameras.set_viewport(entity, camera.viewport);
+        }
+
+        if let Some((align, scale, factor, ref text)) = record.label {
+            components.labels.register(entity);
+
+            let mut builder = components.labels.build();
+            builder.text(text).alignment(align);
+
+            match scale {
+                render::TextScale::Pixel => builder.pixel_scale_factor(factor),
+                render::TextScale::Aspect => builder.aspect_scale_factor(factor),
+            };
+
+            builder.for_entity(entity);
+        }
+
+        if let Some((align, factor, ref text)) = record.text {
+            components.texts.register(entity);
+            components.texts.build()
+                .text(text)
+                .alignment(align)
+                .scale_factor(factor)
+                .for_entity(entity);
+        }
+
+        if let Some(ref softbody) = record.softbody {
+            components.softbodies.register(entity);
+
+            if let Some(softbody) = softbody {
+                let position = record.transform.unwrap().0; // Validated above
+
+                let mut builder = components.softbodies.build_instance();
+
+                builder
+                    .mass(softbody.mass)
+                    .rigidity(softbody.rigidity)
+                    .end_offset(softbody.end_offset)
+                    .initial_pos(position);
+
+                match softbody.source {
+                    Source::BoxLimb(scale) => {
+                        builder.make_box_limb(scale);
+                    },
+
+                    Source::Model(ref name) => {
+                        let model = models.iter()
+                            .find(|data| &data.name == name)
+                            .unwrap(); // Validated above
+
+                        builder.from_model(model);
+                    },
+
+                    Source::Custom => unreachable!(),
+                }
+
+                builder.for_entity(entity);
+            }
+        }
+
+        handles.push(entity);
+    }
+
+    // Activate cameras once all of them are registered, in saved order
+    let mut active = records.iter().zip(&handles)
+        .filter_map(|(record, entity)| {
+            let position = record.camera.as_ref()?.active?;
+            Some((position, components.cameras.get_index(*entity)))
+        })
+        .collect::<Vec<_>>();
+
+    if !active.is_empty() {
+        active.sort();
+
+        let indices = active.iter()
+            .map(|&(_, index)| index)
+            .collect::<Vec<_>>();
+
+        components.cameras.set_active_split(&indices);
+    }
+
+    // Parent once all transforms exist
+    for (record, entity) in records.iter().zip(&handles) {
+        if let Some(parent) = record.parent {
+            components.transforms.parent(*entity, handles[parent]);
+        }
+    }
+
+    for joint in joints {
+        components.softbodies.add_joint_description(
+            components::softbody::JointDescription {
+                parent: handles[joint.parent],
+                child: handles[joint.child],
+                x: joint.x,
+                y: joint.y,
+                z: joint.z,
+                unlocked: joint.unlocked,
+                fwd: joint.fwd,
+                up: joint.up,
+                offset: joint.offset,
+            }
+        );
+    }
+
+    // Instances are created axis-aligned, and joints align their children
+    // with the parent; move every instance back to its saved pose
+    for (record, entity) in records.iter().zip(&handles) {
+        let (position, orientation) = match (
+            &record.softbody,
+            record.transform,
+        ) {
+            (Some(Some(_)), Some((position, orientation, _))) => {
+                (position, orientation)
+            },
+
+            _ => continue,
+        };
+
+        let instance = components.softbodies.get_mut_instance(*entity);
+        let center = instance.center();
+        let current = instance.matched_orientation(center).to_quat();
+
+        instance.rotate_around(orientation * current.conjugate(), center);
+        instance.translate(position - center);
+        instance.lock();
+    }
+
+    for plane in planes {
+        if !components.softbodies.planes().contains(&plane) {
+            components.softbodies.add_plane(plane);
+        }
+    }
+
+    Ok(handles)
+}
+
+#[derive(Default)]
+struct Record {
+    name: Option<String>,
+    tags: Vec<String>,
+    transform: Option<(alg::Vec3, alg::Quat, alg::Vec3)>,
+    parent: Option<usize>,
+    draw: Option<Option<String>>, // Registered, with optional model
+    light: Option<render::Light>,
+    camera: Option<CameraRecord>,
+    label: Option<(render::TextAlign, render::TextScale, f32, String)>,
+    text: Option<(render::TextAlign, f32, String)>,
+    softbody: Option<Option<SoftbodyRecord>>, // Registered, with optional instance
+}
+
+struct CameraRecord {
+    projection: Projection,
+    fov: f32,
+    size: f32,
+    near: f32,
+    far: f32,
+    viewport: render::Viewport,
+    active: Option<usize>, // Position among the active cameras
+}
+
+struct SoftbodyRecord {
+    source: Source,
+    mass: f32,
+    rigidity: f32,
+    end_offset: f32,
+}
+
+struct JointRecord {
+    parent: usize,
+    child: usize,
+    x: (f32, f32),
+    y: (f32, f32),
+    z: (f32, f32),
+    unlocked: bool,
+    fwd: alg::Vec3,
+    up: alg::Vec3,
+    offset: alg::Vec3,
+}
+
+fn parse_floats(input: &str, count: usize) -> Option<Vec<f32>> {
+    let values = input.split_whitespace()
+        .map(|s| s.parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    if values.len() == count { Some(values) } else { None }
+}
+
+fn vec3(values: &[f32]) -> alg::Vec3 {
+    alg::Vec3::new(values[0], values[1], values[2])
+}
+
+fn encode_string(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+
+    for c in input.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '"' => output.push_str("\\\""),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            _ => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+fn decode_string(input: &str) -> Option<String> {
+    if input.len() < 2 || !input.starts_with('"') || !input.ends_with('"') {
+        return None;
+    }
+
+    let mut output = String::with_capacity(input.len() - 2);
+    let mut chars = input[1..input.len() - 1].chars();
+
+    while let Some(c) = chars.next() {
+        output.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                '"' => '"',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                _ => return None,
+            },
+
+            '"' => return None, // Unescaped quote
+            _ => c,
+        });
+    }
+
+    Some(output)
+}
+
+fn encode_align(align: render::TextAlign) -> &'static str {
+    match align {
+        render::TextAlign::Left => "left",
+        render::TextAlign::Center => "center",
+        render::TextAlign::Right => "right",
+    }
+}
+
+fn decode_align(input: &str) -> Option<render::TextAlign> {
+    match input {
+        "left" => Some(render::TextAlign::Left),
+        "center" => Some(render::TextAlign::Center),
+        "right" => Some(render::TextAlign::Right),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use components;
+    use components::camera;
+    use headless;
+    use scene::*;
+
+    #[test]
+    fn encode_decode_string() {
+        for input in &["", " padded ", "line\nbreak", "\"quoted\" \\ \t\r"] {
+            let encoded = encode_string(input);
+            assert!(!encoded.contains('\n'));
+            assert!(decode_string(&encoded) == Some(input.to_string()));
+        }
+
+        assert!(decode_string("unquoted").is_none());
+        assert!(decode_string("\"").is_none());
+        assert!(decode_string("\"a\"b\"").is_none());
+        assert!(decode_string("\"\\x\"").is_none());
+    }
+
+    #[test]
+    fn parse_floats_count() {
+        assert!(parse_floats("1 2.5 -3", 3) == Some(vec![1.0, 2.5, -3.0]));
+        assert!(parse_floats("1 2.5", 3).is_none());
+        assert!(parse_floats("1 x 3", 3).is_none());
+    }
+
+    fn load_scene(name: &str, scene: &str) -> std::io::Result<usize> {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        std::fs::write(path, format!("{}\n{}", HEADER, scene)).unwrap();
+
+        let mut runner = headless::Runner::new(vec![], headless::Empty);
+        let result = load(
+            path,
+            &[],
+            &mut runner.entities,
+            &mut runner.components,
+        );
+
+        std::fs::remove_file(path).unwrap();
+        result.map(|handles| handles.len())
+    }
+
+    #[test]
+    fn reject_invalid_parents() {
+        let transform = "transform 0 0 0 0 0 0 1 1 1 1";
+
+        let self_parent = format!("entity 0\n{}\nparent 0\n", transform);
+        let cycle = format!(
+            "entity 0\n{0}\nparent 2\n\
+            entity 1\n{0}\nparent 0\n\
+            entity 2\n{0}\nparent 1\n",
+            transform,
+        );
+        let orphan = "entity 0\nparent 1\n\
+            entity 1\ntransform 0 0 0 0 0 0 1 1 1 1\n";
+        let no_parent_transform = format!(
+            "entity 0\n{}\nparent 1\nentity 1\n",
+            transform,
+        );
+
+        for (name, scene) in &[
+            ("nmg-scene-self.txt", self_parent.as_str()),
+            ("nmg-scene-cycle.txt", cycle.as_str()),
+            ("nmg-scene-orphan.txt", orphan),
+            ("nmg-scene-parentless.txt", no_parent_transform.as_str()),
+        ] {
+            let error = load_scene(name, scene).unwrap_err();
+            assert!(error.kind() == std::io::ErrorKind::InvalidData);
+        }
+
+        let chain = format!(
+            "entity 0\n{0}\nentity 1\n{0}\nparent 0\n\
+            entity 2\n{0}\nparent 1\n",
+            transform,
+        );
+
+        assert!(load_scene("nmg-scene-chain.txt", &chain).unwrap() == 3);
+    }
+
+    #[test]
+    fn reject_softbody_without_transform() {
+        for (name, scene) in &[
+            ("nmg-scene-body.txt", "entity 0\nsoftbody box 1 1 1 1 1 0\n"),
+            ("nmg-scene-empty-body.txt", "entity 0\nsoftbody\n"),
+        ] {
+            let error = load_scene(name, scene).unwrap_err();
+            assert!(error.kind() == std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn merge_planes() {
+        let path = std::env::temp_dir().join("nmg-scene-planes.txt");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, format!("{}\nplane 0 1 0 0.5\n", HEADER))
+            .unwrap();
+
+        let mut runner = headless::Runner::new(vec![], headless::Empty);
+
+        // Loading the same scene twice keeps a single copy of each plane
+        for _ in 0..2 {
+            load(path, &[], &mut runner.entities, &mut runner.components)
+                .unwrap();
+        }
+
+        std::fs::remove_file(path).unwrap();
+        assert!(runner.components.softbodies.planes().len() == 1);
+    }
+
+    #[test]
+    fn save_load() {
+        let models = vec![
+            render::ModelData {
+                name: "cube".to_string(),
+                computed_normals: false,
+                vertices: vec![],
+                indices: vec![],
+            },
+
+            render::ModelData {
+                name: " padded cube ".to_string(),
+                computed_normals: false,
+                vertices: vec![],
+                indices: vec![],
+            },
+        ];
+
+        fn limb(
+            entity: entity::Handle,
+            position: alg::Vec3,
+            components: &mut components::Container,
+        ) {
+            let rotation = alg::Quat::axis_angle(alg::Vec3::up(), 0.5);
+
+            components.transforms.register(entity);
+            components.softbodies.register(entity);
+            components.softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(position)
+                .for_entity(entity);
+
+            let instance = components.softbodies.get_mut_instance(entity);
+            instance.rotate_around(rotation, position);
+            instance.lock();
+        }
+
+        let mut source = headless::Runner::new(
+            models.clone(),
+            headless::Empty,
+        );
+
+        let (parent, arm, child) = {
+            let entities = &mut source.entities;
+            let components = &mut source.components;
+
+            let parent = entities.add();
+            entities.set_name(parent, "parent");
+            entities.add_tag(parent, "limb");
+            limb(parent, alg::Vec3::new(0.0, 2.0, 0.0), components);
+
+            components.draws.register(parent);
+            components.draws.bind_model(parent, "cube");
+
+            let arm = entities.add();
+            limb(arm, alg::Vec3::new(0.0, 2.0, 1.0), components);
+
+            components.softbodies.build_joint()
+                .with_parent(parent)
+                .x(-30.0, 30.0)
+                .y(-10.0, 10.0)
+                .z(-45.0, 90.0)
+                .offset(alg::Vec3::new(0.0, 0.0, 0.5))
+                .for_child(arm);
+
+            let child = entities.add();
+            components.transforms.register(child);
+            components.transforms.parent(child, parent);
+
+            components.cameras.register(child);
+            components.cameras.set_fov(child, 90.0);
+            components.cameras.set_projection(child, Projection::Orthographic);
+            components.cameras.set_size(child, 12.0);
+            components.cameras.set_viewport(
+                child,
+                render::Viewport::new(0.5, 0.0, 0.5, 1.0),
+            );
+
+            components.draws.register(child);
+            components.draws.bind_model(child, " padded cube ");
+
+            // Render the arm's camera first
+            components.cameras.register(arm);
+            components.cameras.set_active_split(&[1, 0]);
+
+            components.lights.register(child);
+            components.lights.build()
+                .point_with_radius(4.0)
+                .color(graphics::Color::new(1.0, 0.5, 0.25))
+                .intensity(2.0)
+                .for_entity(child);
+
+            components.labels.register(child);
+            components.labels.build()
+                .text(" two\nlines ")
+                .alignment(render::TextAlign::Left)
+                .aspect_scale_factor(0.5)
+                .for_entity(child);
+
+            components.texts.register(child);
+            components.texts.build()
+                .text("\"quoted\"")
+                .alignment(render::TextAlign::Right)
+                .scale_factor(2.0)
+                .for_entity(child);
+
+            components.softbodies.add_plane(
+                alg::Plane::new(alg::Vec3::up(), 0.0),
+            );
+
+            (parent, arm, child)
+        };
+
+        // Write limb poses to their transforms
+        source.step_fixed();
+        let sources = [parent, arm];
+
+        let path = std::env::temp_dir().join("nmg-scene-test.txt");
+        let path = path.to_str().unwrap();
+
+        save(
+            path,
+            &[parent, arm, child],
+            &source.entities,
+            &source.components,
+        ).unwrap();
+
+        let mut target = headless::Runner::new(
+            models.clone(),
+            headless::Empty,
+        );
+        let handles = load(
+            path,
+            &models,
+            &mut target.entities,
+            &mut target.components,
+        ).unwrap();
+
+        std::fs::remove_file(path).unwrap();
+
+        assert!(handles.len() == 3);
+        let (parent, arm, child) = (handles[0], handles[1], handles[2]);
+        let components = &target.components;
+
+        assert!(target.entities.find("parent") == Some(parent));
+        assert!(target.entities.has_tag(parent, "limb"));
+        assert!(components.transforms.get_parent(child) == Some(parent));
+        assert!(components.draws.get_model(parent) == Some("cube"));
+        assert!(components.draws.get_model(arm).is_none());
+        assert!(components.draws.get_model(child) == Some(" padded cube "));
+
+        assert!(components.cameras.get_fov(child) == 90.0);
+        assert!(components.cameras.get_size(child) == 12.0);
+        assert!(
+            components.cameras.get_projection(child)
+                == Projection::Orthographic
+        );
+        assert!(
+            components.cameras.get_viewport(child)
+                == render::Viewport::new(0.5, 0.0, 0.5, 1.0)
+        );
+
+        // Registered in a different order, but rendered in the same one
+        assert!(components.cameras.get_active(arm) == Some(0));
+        assert!(components.cameras.get_active(child) == Some(1));
+        assert!(components.cameras.get_size(arm) == camera::DEFAULT_SIZE);
+        assert!(
+            components.cameras.get_projection(arm) == Projection::Perspective
+        );
+
+        let light = components.lights.fetch(child).unwrap();
+        assert!(light.radius == 4.0);
+        assert!(light.intensity == 2.0);
+        assert!(light.color == graphics::Color::new(1.0, 0.5, 0.25));
+
+        let label = components.labels.fetch(child).unwrap();
+        assert!(label.text == " two\nlines ");
+        assert!(encode_align(label.align) == "left");
+        assert!(label.scale == render::TextScale::Aspect);
+        assert!(label.scale_factor == 0.5);
+
+        let text = components.texts.fetch(child).unwrap();
+        assert!(text.text == "\"quoted\"");
+        assert!(encode_align(text.align) == "right");
+        assert!(text.scale_factor == 2.0);
+
+        // Joint limits are stored in radians, so compare approximately
+        let joints = components.softbodies.joints();
+        assert!(joints.len() == 1);
+
+        let joint = &joints[0];
+        let close = |a: (f32, f32), b: (f32, f32)| {
+            (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+        };
+
+        assert!(joint.parent == parent && joint.child == arm);
+        assert!(close(joint.x, (-30.0, 30.0)));
+        assert!(close(joint.y, (-10.0, 10.0)));
+        assert!(close(joint.z, (-45.0, 90.0)));
+        assert!(!joint.unlocked);
+        assert!(joint.offset == alg::Vec3::new(0.0, 0.0, 0.5));
+        assert!(components.softbodies.planes().len() == 1);
+
+        // Limbs are rebuilt in their saved poses, joints included
+        for (loaded, saved) in [parent, arm].iter().zip(&sources) {
+            let instance = components.softbodies.get_instance(*loaded);
+            let expected = source.components.softbodies.get_instance(*saved);
+
+            match instance.source() {
+                Source::BoxLimb(scale) => assert!(*scale == alg::Vec3::one()),
+                _ => panic!("Expected box limb"),
+            }
+
+            let center = instance.center();
+            assert!(center.dist(expected.center()) < 1e-3);
+
+            let fwd = instance.matched_orientation(center)
+                * alg::Vec3::fwd();
+
+            let expected_fwd = expected.matched_orientation(expected.center())
+                * alg::Vec3::fwd();
+
+            assert!(fwd.dist(expected_fwd) < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use render;
+use entity;
+use components;
+use input;
+use debug;
+use clock;
+
+use ::{Metadata, ScreenData, Start, Update, FixedUpdate};
+use components::softbody::Iterate;
+
+/// Change to the scene stack, returned from `Scene::update()`
+pub enum Transition {
+    None,
+    Push(Box<dyn Scene>), // Pause the current scene and enter a new one
+    Pop, // Exit the current scene and resume the one below it
+    Replace(Box<dyn Scene>), // Exit the current scene and enter a new one
+}
+
+/// A game state (e.g. menu, level, pause overlay) managed by a `Stack`. \
+/// Only the scene on top of the stack is updated. \
+/// Entities created during a scene's callbacks are owned by it, and are
+/// destroyed (via `Container::destroy()`) when the scene exits.
+pub trait Scene {
+    #[allow(unused_variables)]
+    fn enter(
+        &mut self,
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
+
+    #[allow(unused_variables)]
+    fn exit(
+        &mut self,
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
+
+    /// Called when another scene is pushed on top of this one
+    #[allow(unused_variables)]
+    fn pause(
+        &mut self,
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
+
+    /// Called when this scene is on top of the stack again
+    #[allow(unused_variables)]
+    fn resume(
+        &mut self,
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
+
+    #[allow(unused_variables)]
+    fn update(
+        &mut self,
+        time:  f64,
+        delta: f64,
+        metadata: Metadata,
+        control: &mut clock::Control,
+        screen: ScreenData,
+        parameters: &mut render::Parameters,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+        input: &mut input::Manager,
+        debug: &mut debug::Handler,
+    ) -> Transition { Transition::None }
+
+    #[allow(unused_variables)]
+    fn fixed_update(
+        &mut self,
+        time: f64,
+        fixed_delta: f32,
+        metadata: Metadata,
+        screen: ScreenData,
+        parameters: &mut render::Parameters,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+        input: &mut input::Manager,
+        debug: &mut debug::Handler,
+    ) { }
+
+    #[allow(unused_variables)]
+    fn iterate(
+        &mut self,
+        fixed_delta: f32,
+        iterations: usize,
+        softbodies: &mut components::softbody::Manager,
+    ) { }
+}
+
+// Scene along with the entities created during its callbacks
+struct Entry {
+    scene: Box<dyn Scene>,
+    owned: Vec<entity::Handle>,
+    pruned: usize, // Length of `owned` after the last cleanup
+}
+
+impl Entry {
+    fn new(scene: Box<dyn Scene>) -> Entry {
+        Entry {
+            scene,
+            owned: Vec::new(),
+            pruned: 0,
+        }
+    }
+
+    // Take the entities recorded since `begin_recording()`,
+    // dropping stale handles whenever the list has doubled
+    fn own(&mut self, entities: &mut entity::Manager) {
+        self.owned.extend(entities.end_recording());
+
+        if self.owned.len() > 2 * self.pruned + 64 {
+            self.owned.retain(|entity| entities.check(*entity));
+            self.pruned = self.owned.len();
+        }
+    }
+}
+
+/// Stack of scenes, usable as the game object passed to `go()`
+pub struct Stack {
+    scenes: Vec<Entry>,
+    initial: Option<Box<dyn Scene>>, // Entered on start
+}
+
+impl Stack {
+    pub fn new(initial: Box<dyn Scene>) -> Stack {
+        Stack {
+            scenes: Vec::new(),
+            initial: Some(initial),
+        }
+    }
+
+    /// Number of scenes on the stack
+    pub fn depth(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// Apply a transition immediately (e.g. from outside of a scene)
+    pub fn transition(
+        &mut self,
+        transition: Transition,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) {
+        match transition {
+            Transition::None => (),
+
+            Transition::Push(scene) => {
+                if let Some(top) = self.scenes.last_mut() {
+                    entities.begin_recording();
+                    top.scene.pause(entities, components);
+                    top.own(entities);
+                }
+
+                self.enter(scene, entities, components);
+            },
+
+            Transition::Pop => {
+                self.exit(entities, components);
+
+                if let Some(top) = self.scenes.last_mut() {
+                    entities.begin_recording();
+                    top.scene.resume(entities, components);
+                    top.own(entities);
+                }
+            },
+
+            Transition::Replace(scene) => {
+                self.exit(entities, components);
+                self.enter(scene, entities, components);
+            },
+        }
+    }
+
+    fn enter(
+        &mut self,
+        scene: Box<dyn Scene>,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) {
+        let mut entry = Entry::new(scene);
+
+        entities.begin_recording();
+        entry.scene.enter(entities, components);
+        entry.own(entities);
+
+        self.scenes.push(entry);
+    }
+
+    // Exit the top scene and destroy the entities it owns
+    fn exit(
+        &mut self,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) {
+        if let Some(mut entry) = self.scenes.pop() {
+            entities.begin_recording();
+            entry.scene.exit(entities, components);
+            entry.own(entities);
+
+            // Handles destroyed in the meantime are ignored
+            for entity in entry.owned {
+                components.destroy(entities, entity);
+            }
+        }
+    }
+}
+
+impl Start for Stack {
+    fn start(
+        &mut self,
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) {
+        if let Some(scene) = self.initial.take() {
+            self.transition(Transition::Push(scene), entities, components);
+        }
+    }
+}
+
+impl Update for Stack {
+    fn update(
+        &mut self,
+        time:  f64,
+        delta: f64,
+        metadata: Metadata,
+        control: &mut clock::Control,
+        screen: ScreenData,
+        parameters: &mut render::Parameters,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+        input: &mut input::Manager,
+        debug: &mut debug::Handler,
+    ) {
+        let transition = match self.scenes.last_mut() {
+            Some(top) => {
+                entities.begin_recording();
+
+                let transition = top.scene.update(
+                    time,
+                    delta,
+                    metadata,
+                    control,
+                    screen,
+                    parameters,
+                    entities,
+                    components,
+                    input,
+                    debug,
+                );
+
+                top.own(entities);
+                transition
+            },
+
+            None => return,
+        };
+
+        self.transition(transition, entities, components);
+    }
+}
+
+impl FixedUpdate for Stack {
+    fn fixed_update(
+        &mut self,
+        time: f64,
+        fixed_delta: f32,
+        metadata: Metadata,
+        screen: ScreenData,
+        parameters: &mut render::Parameters,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+        input: &mut input::Manager,
+        debug: &mut debug::Handler,
+    ) {
+        if let Some(top) = self.scenes.last_mut() {
+            entities.begin_recording();
+
+            top.scene.fixed_update(
+                time,
+                fixed_delta,
+                metadata,
+                screen,
+                parameters,
+                entities,
+                components,
+                input,
+                debug,
+            );
+
+            top.own(entities);
+        }
+    }
+}
+
+impl Iterate for Stack {
+    fn iterate(
+        &mut self,
+        fixed_delta: f32,
+        iterations: usize,
+        softbodies: &mut components::softbody::Manager,
+    ) {
+        if let Some(top) = self.scenes.last_mut() {
+            top.scene.iterate(fixed_delta, iterations, softbodies);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity;
+    use components;
+    use components::Component;
+    use headless;
+    use Start;
+    use stack::*;
+
+    // Spawn a single entity owned by the current scene
+    fn spawn(
+        entities:   &mut entity::Manager,
+        components: &mut components::Container,
+    ) {
+        let entity = entities.add();
+        components.transforms.register(entity);
+    }
+
+    // Pushes a level on the first update
+    struct Menu {
+        pushed: bool,
+    }
+
+    impl Scene for Menu {
+        fn enter(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            spawn(entities, components);
+        }
+
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            control: &mut clock::Control,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &mut input::Manager,
+            debug: &mut debug::Handler,
+        ) -> Transition {
+            if self.pushed {
+                return Transition::None;
+            }
+
+            self.pushed = true;
+            Transition::Push(Box::new(Level))
+        }
+    }
+
+    // Spawns two entities, then pops itself on the first update
+    struct Level;
+
+    impl Scene for Level {
+        fn enter(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            spawn(entities, components);
+            spawn(entities, components);
+        }
+
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            control: &mut clock::Control,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &mut input::Manager,
+            debug: &mut debug::Handler,
+        ) -> Transition {
+            Transition::Pop
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut runner = headless::Runner::new(
+            vec![],
+            Stack::new(Box::new(Menu { pushed: false })),
+        );
+
+        assert!(runner.game().depth() == 1);
+        assert!(runner.entities.count() == 1);
+
+        // Menu pushes level
+        runner.step(0.0);
+        assert!(runner.game().depth() == 2);
+        assert!(runner.entities.count() == 3);
+        assert!(runner.components.transforms.count() == 3);
+
+        // Level pops itself, destroying its entities
+        runner.step(0.0);
+        assert!(runner.game().depth() == 1);
+        assert!(runner.entities.count() == 1);
+        assert!(runner.components.transforms.count() == 1);
+    }
+
+    // Spawns an entity whenever it is paused or resumed
+    struct Base;
+
+    impl Scene for Base {
+        fn pause(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            spawn(entities, components);
+        }
+
+        fn resume(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            spawn(entities, components);
+        }
+    }
+
+    #[test]
+    fn pause_resume_ownership() {
+        let mut entities = entity::Manager::new(4);
+        let mut components = ::init_components(
+            render::Instances::new(0, &vec![], None),
+        );
+
+        let mut stack = Stack::new(Box::new(Base));
+        stack.start(&mut entities, &mut components);
+
+        stack.transition(
+            Transition::Push(Box::new(Level)),
+            &mut entities,
+            &mut components,
+        );
+
+        assert!(entities.count() == 3);
+
+        // Level's entities go, and base spawns another on resume
+        stack.transition(Transition::Pop, &mut entities, &mut components);
+        assert!(entities.count() == 2);
+
+        // Entities spawned in pause and resume belong to base
+        stack.transition(Transition::Pop, &mut entities, &mut components);
+        assert!(stack.depth() == 0);
+        assert!(entities.count() == 0);
+        assert!(components.transforms.count() == 0);
+    }
+
+    #[test]
+    fn unowned_entities() {
+        let mut entities = entity::Manager::new(2);
+        let mut components = ::init_components(
+            render::Instances::new(0, &vec![], None),
+        );
+
+        let mut stack = Stack::new(Box::new(Level));
+        stack.start(&mut entities, &mut components);
+        assert!(entities.count() == 2);
+
+        // Entities created outside of a scene are not owned
+        let entity = entities.add();
+        stack.transition(Transition::Pop, &mut entities, &mut components);
+
+        assert!(stack.depth() == 0);
+        assert!(entities.count() == 1);
+        assert!(entities.check(entity));
+    }
+
+    #[test]
+    fn record_new_entities() {
+        let mut entities = entity::Manager::new(4);
+        let before = entities.add();
+
+        entities.begin_recording();
+        let first = entities.add();
+        let second = entities.add();
+        entities.remove(second);
+
+        assert!(entities.end_recording() == vec![first, second]);
+
+        // Nothing is collected outside of a recording
+        entities.add();
+        assert!(entities.end_recording().is_empty());
+        assert!(entities.check(before));
+    }
+}
@@ -168,7 +168,7 @@ mod tests {
             let mut ptr = raw.as_mut_ptr() as *const alg::Mat4;
             let test_mat = *ptr;
 
-            ptr = ptr.offset(1);
+            ptr = ptr.offset(2); // Skip normal matrix
             let mut ptr = ptr as *const render::Light;
             ptr = ptr.offset(render::MAX_INSTANCE_LIGHTS as isize);
 